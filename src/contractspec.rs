@@ -1,21 +1,401 @@
 use std::{io::Cursor, rc::Rc};
 
 use soroban_env_host::{
-    xdr::{ReadXdr, ScSpecEntry, ScSpecFunctionV0},
+    xdr::{
+        ReadXdr, ScBigInt, ScMap, ScMapEntry, ScObject, ScSpecEntry, ScSpecFunctionV0,
+        ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeResult, ScSpecTypeSet,
+        ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec, ScSpecUdtStructV0, ScSpecUdtUnionV0,
+        ScStatic, ScStatus, ScVal,
+    },
     Vm,
 };
 
+/// Identifies a spec entry by its kind and name, e.g. `fn transfer` or `struct Config`.
+pub fn entry_name(entry: &ScSpecEntry) -> String {
+    match entry {
+        ScSpecEntry::FunctionV0(f) => format!("fn {}", f.name.to_string_lossy()),
+        ScSpecEntry::UdtStructV0(s) => format!("struct {}", s.name.to_string_lossy()),
+        ScSpecEntry::UdtUnionV0(u) => format!("union {}", u.name.to_string_lossy()),
+    }
+}
+
 pub fn function_spec(vm: &Rc<Vm>, name: &str) -> Option<ScSpecFunctionV0> {
-    let spec = vm.custom_section("contractspecv0")?;
+    function_specs(vm)
+        .into_iter()
+        .find(|f| matches!(f.name.to_string(), Ok(n) if n == name))
+}
+
+pub fn function_specs(vm: &Rc<Vm>) -> Vec<ScSpecFunctionV0> {
+    spec_entries(vm)
+        .into_iter()
+        .filter_map(|spec_entry| match spec_entry {
+            ScSpecEntry::FunctionV0(f) => Some(f),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn spec_entries(vm: &Rc<Vm>) -> Vec<ScSpecEntry> {
+    let spec = vm.custom_section("contractspecv0").unwrap_or_default();
     let mut cursor = Cursor::new(spec);
-    for spec_entry in ScSpecEntry::read_xdr_iter(&mut cursor).flatten() {
-        if let ScSpecEntry::FunctionV0(f) = spec_entry {
-            if let Ok(n) = f.name.to_string() {
-                if n == name {
-                    return Some(f);
+    ScSpecEntry::read_xdr_iter(&mut cursor).flatten().collect()
+}
+
+/// Finds the struct or union with the name.
+pub fn find_udt<'a>(spec: &'a [ScSpecEntry], name: &str) -> Option<&'a ScSpecEntry> {
+    spec.iter().find(|e| match e {
+        ScSpecEntry::UdtStructV0(s) => s.name.to_string_lossy() == name,
+        ScSpecEntry::UdtUnionV0(u) => u.name.to_string_lossy() == name,
+        ScSpecEntry::FunctionV0(_) => false,
+    })
+}
+
+/// Renders a spec type the way it would be written in a Rust contract, with user defined types
+/// referenced by name, e.g. `Map<Symbol, Vec<Identifier>>`.
+pub fn type_to_string(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::U64 => "u64".to_string(),
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Symbol => "Symbol".to_string(),
+        ScSpecTypeDef::Bitset => "Bitset".to_string(),
+        ScSpecTypeDef::Status => "Status".to_string(),
+        ScSpecTypeDef::Bytes => "Bytes".to_string(),
+        ScSpecTypeDef::BigInt => "BigInt".to_string(),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!("Option<{}>", type_to_string(value_type))
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult {
+                ok_type,
+                error_type,
+            } = &**r;
+            format!(
+                "Result<{}, {}>",
+                type_to_string(ok_type),
+                type_to_string(error_type)
+            )
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!("Vec<{}>", type_to_string(element_type))
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!("Set<{}>", type_to_string(element_type))
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            format!(
+                "Map<{}, {}>",
+                type_to_string(key_type),
+                type_to_string(value_type)
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            tuple_to_string(value_types.as_slice())
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => name.to_string_lossy(),
+    }
+}
+
+/// Renders a tuple, or a function's outputs, e.g. `(u32, Symbol)`. A single type is rendered
+/// without parentheses.
+pub fn tuple_to_string(types: &[ScSpecTypeDef]) -> String {
+    match types {
+        [t] => type_to_string(t),
+        _ => format!(
+            "({})",
+            types
+                .iter()
+                .map(type_to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Renders a function's signature, e.g. `fn transfer(from: Identifier, amount: BigInt) -> ()`.
+pub fn function_signature(f: &ScSpecFunctionV0) -> String {
+    let inputs = f
+        .inputs
+        .iter()
+        .map(|i| format!("{}: {}", i.name.to_string_lossy(), type_to_string(&i.type_)))
+        .collect::<Vec<_>>();
+    format!(
+        "fn {}({}) -> {}",
+        f.name.to_string_lossy(),
+        inputs.join(", "),
+        tuple_to_string(f.outputs.as_slice())
+    )
+}
+
+/// Renders a struct's definition, e.g. `struct Config { admin: Identifier, limit: u32 }`.
+pub fn struct_definition(s: &ScSpecUdtStructV0) -> String {
+    let fields = s
+        .fields
+        .iter()
+        .map(|f| format!("{}: {}", f.name.to_string_lossy(), type_to_string(&f.type_)))
+        .collect::<Vec<_>>();
+    format!(
+        "struct {} {{ {} }}",
+        s.name.to_string_lossy(),
+        fields.join(", ")
+    )
+}
+
+/// Renders a union's definition as a Rust enum, e.g. `enum DataKey { Admin, Balance(Identifier) }`.
+pub fn union_definition(u: &ScSpecUdtUnionV0) -> String {
+    let cases = u
+        .cases
+        .iter()
+        .map(|c| match &c.type_ {
+            Some(t) => format!("{}({})", c.name.to_string_lossy(), type_to_string(t)),
+            None => c.name.to_string_lossy(),
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "enum {} {{ {} }}",
+        u.name.to_string_lossy(),
+        cases.join(", ")
+    )
+}
+
+const MAX_EXAMPLE_DEPTH: usize = 4;
+
+/// A placeholder value of the type, for generated examples and tests. User defined types are
+/// encoded the way the SDK encodes them: structs as a map keyed by field name, and unions as a vec
+/// of the case name and value. There is none for user defined types missing from the spec.
+pub fn example_value(t: &ScSpecTypeDef, spec: &[ScSpecEntry]) -> Option<ScVal> {
+    example_value_at(t, spec, 0)
+}
+
+fn example_value_at(t: &ScSpecTypeDef, spec: &[ScSpecEntry], depth: usize) -> Option<ScVal> {
+    // Recursive types would otherwise expand forever
+    if depth > MAX_EXAMPLE_DEPTH {
+        return None;
+    }
+    let example = |t: &ScSpecTypeDef| example_value_at(t, spec, depth + 1);
+    let object = |o: ScObject| ScVal::Object(Some(o));
+    let symbol = |s: String| s.as_bytes().try_into().ok().map(ScVal::Symbol);
+    Some(match t {
+        ScSpecTypeDef::U32 => ScVal::U32(1),
+        ScSpecTypeDef::I32 => ScVal::I32(-1),
+        ScSpecTypeDef::U64 => ScVal::U63(1),
+        ScSpecTypeDef::I64 => object(ScObject::I64(-1)),
+        ScSpecTypeDef::BigInt => object(ScObject::BigInt(ScBigInt::Positive(
+            vec![100].try_into().ok()?,
+        ))),
+        ScSpecTypeDef::Bitset => ScVal::Bitset(1),
+        ScSpecTypeDef::Status => ScVal::Status(ScStatus::Ok),
+        ScSpecTypeDef::Bool => ScVal::Static(ScStatic::True),
+        ScSpecTypeDef::Symbol => symbol("hello".to_string())?,
+        ScSpecTypeDef::Bytes => object(ScObject::Bytes(vec![1, 2, 3].try_into().ok()?)),
+        // Empty options, vecs and maps end a recursive type early
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            example(value_type).unwrap_or(ScVal::Object(None))
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult { ok_type, .. } = &**r;
+            example(ok_type)?
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            let values: Vec<ScVal> = example(element_type).into_iter().collect();
+            object(ScObject::Vec(values.try_into().ok()?))
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            let values: Vec<ScVal> = example(element_type).into_iter().collect();
+            object(ScObject::Vec(values.try_into().ok()?))
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            let entries = match (example(key_type), example(value_type)) {
+                (Some(key), Some(val)) => vec![ScMapEntry { key, val }],
+                _ => vec![],
+            };
+            object(ScObject::Map(ScMap::sorted_from(entries).ok()?))
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let values = value_types
+                .iter()
+                .map(example)
+                .collect::<Option<Vec<_>>>()?;
+            object(ScObject::Vec(values.try_into().ok()?))
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            match find_udt(spec, &name.to_string_lossy())? {
+                ScSpecEntry::UdtStructV0(s) => {
+                    let entries = s
+                        .fields
+                        .iter()
+                        .map(|f| {
+                            Some(ScMapEntry {
+                                key: symbol(f.name.to_string_lossy())?,
+                                val: example(&f.type_)?,
+                            })
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    object(ScObject::Map(ScMap::sorted_from(entries).ok()?))
                 }
+                // The first case that has an example
+                ScSpecEntry::UdtUnionV0(u) => u.cases.iter().find_map(|c| {
+                    let mut values = vec![symbol(c.name.to_string_lossy())?];
+                    if let Some(t) = &c.type_ {
+                        values.push(example(t)?);
+                    }
+                    Some(object(ScObject::Vec(values.try_into().ok()?)))
+                })?,
+                ScSpecEntry::FunctionV0(_) => return None,
             }
         }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_env_host::xdr::ScSpecFunctionInputV0;
+
+    use super::*;
+
+    fn udt(name: &str) -> ScSpecTypeDef {
+        ScSpecTypeDef::Udt(ScSpecTypeUdt {
+            name: name.as_bytes().try_into().unwrap(),
+        })
+    }
+
+    fn vec(t: ScSpecTypeDef) -> ScSpecTypeDef {
+        ScSpecTypeDef::Vec(Box::new(ScSpecTypeVec { element_type: t }))
+    }
+
+    #[test]
+    fn test_type_to_string() {
+        let cases = vec![
+            (ScSpecTypeDef::U32, "u32"),
+            (ScSpecTypeDef::BigInt, "BigInt"),
+            (udt("Config"), "Config"),
+            (vec(udt("Identifier")), "Vec<Identifier>"),
+            (
+                ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                    value_type: ScSpecTypeDef::I64,
+                })),
+                "Option<i64>",
+            ),
+            (
+                ScSpecTypeDef::Result(Box::new(ScSpecTypeResult {
+                    ok_type: ScSpecTypeDef::Bool,
+                    error_type: ScSpecTypeDef::Status,
+                })),
+                "Result<bool, Status>",
+            ),
+            (
+                ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+                    key_type: ScSpecTypeDef::Symbol,
+                    value_type: vec(ScSpecTypeDef::Bytes),
+                })),
+                "Map<Symbol, Vec<Bytes>>",
+            ),
+            (
+                ScSpecTypeDef::Set(Box::new(ScSpecTypeSet {
+                    element_type: ScSpecTypeDef::U64,
+                })),
+                "Set<u64>",
+            ),
+            (
+                ScSpecTypeDef::Tuple(Box::new(ScSpecTypeTuple {
+                    value_types: vec![ScSpecTypeDef::U32, udt("Config")].try_into().unwrap(),
+                })),
+                "(u32, Config)",
+            ),
+        ];
+        for (t, expected) in cases {
+            assert_eq!(type_to_string(&t), expected);
+        }
+    }
+
+    #[test]
+    fn test_tuple_to_string() {
+        assert_eq!(tuple_to_string(&[]), "()");
+        assert_eq!(tuple_to_string(&[ScSpecTypeDef::U32]), "u32");
+        assert_eq!(
+            tuple_to_string(&[ScSpecTypeDef::U32, ScSpecTypeDef::Bool]),
+            "(u32, bool)"
+        );
+    }
+
+    #[test]
+    fn test_function_signature() {
+        let f = ScSpecFunctionV0 {
+            name: "transfer".as_bytes().try_into().unwrap(),
+            inputs: vec![
+                ScSpecFunctionInputV0 {
+                    name: "to".as_bytes().try_into().unwrap(),
+                    type_: udt("Identifier"),
+                },
+                ScSpecFunctionInputV0 {
+                    name: "amount".as_bytes().try_into().unwrap(),
+                    type_: ScSpecTypeDef::BigInt,
+                },
+            ]
+            .try_into()
+            .unwrap(),
+            outputs: vec![].try_into().unwrap(),
+        };
+        assert_eq!(
+            function_signature(&f),
+            "fn transfer(to: Identifier, amount: BigInt) -> ()"
+        );
+    }
+
+    #[test]
+    fn test_example_value() {
+        use soroban_env_host::xdr::ScSpecUdtUnionCaseV0;
+
+        // A recursive union, with the recursive case first
+        let spec = vec![ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+            name: "List".as_bytes().try_into().unwrap(),
+            cases: vec![
+                ScSpecUdtUnionCaseV0 {
+                    name: "Cons".as_bytes().try_into().unwrap(),
+                    type_: Some(udt("List")),
+                },
+                ScSpecUdtUnionCaseV0 {
+                    name: "Nil".as_bytes().try_into().unwrap(),
+                    type_: None,
+                },
+            ]
+            .try_into()
+            .unwrap(),
+        })];
+        assert!(example_value(&udt("List"), &spec).is_some());
+        assert_eq!(example_value(&udt("Missing"), &spec), None);
+        assert_eq!(
+            example_value(&vec(udt("Missing")), &spec),
+            Some(ScVal::Object(Some(ScObject::Vec(
+                Vec::<ScVal>::new().try_into().unwrap()
+            ))))
+        );
+        assert_eq!(
+            example_value(
+                &ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                    value_type: udt("Missing"),
+                })),
+                &spec
+            ),
+            Some(ScVal::Object(None))
+        );
     }
-    None
 }
@@ -1,21 +1,65 @@
-use std::{fmt::Debug, fs, io};
+use std::{fmt::Debug, fs, io, rc::Rc};
 
 use clap::Parser;
-use soroban_env_host::xdr::Error as XdrError;
-
-use hex::FromHexError;
+use soroban_env_host::{
+    budget::Budget,
+    im_rc::OrdMap,
+    meta,
+    storage::Storage,
+    xdr::{
+        Error as XdrError, HostFunction, LedgerEntry, LedgerKey, ScHostStorageErrorCode, ScObject,
+        ScSpecEntry, ScStatus, ScVal,
+    },
+    Host, HostError, Vm,
+};
+use stellar_strkey::StrkeyPublicKeyEd25519;
 
+use crate::contractspec;
 use crate::snapshot;
+use crate::specdiff;
+use crate::strval::{self, StrValError};
 use crate::utils;
+use crate::wasm;
 
 #[derive(Parser, Debug)]
 pub struct Cmd {
-    #[clap(long = "id")]
-    /// Contract ID to deploy to
-    contract_id: String,
+    #[clap(long = "id", conflicts_with = "salt")]
+    /// Contract ID to deploy to, derived from the WASM hash if not provided
+    contract_id: Option<String>,
     /// WASM file to deploy
     #[clap(long, parse(from_os_str))]
     wasm: std::path::PathBuf,
+    /// Salt (32 bytes, hex-encoded) used to derive the contract ID the way the network does
+    #[clap(long)]
+    salt: Option<String>,
+    /// Source account (G... strkey) creating the contract, to derive the contract ID from
+    #[clap(long, requires = "salt", conflicts_with = "ed25519")]
+    source_account: Option<String>,
+    /// Ed25519 public key (G... strkey or hex) creating the contract, to derive the contract ID
+    /// from
+    #[clap(long, requires = "salt")]
+    ed25519: Option<String>,
+    /// Overwrite an existing contract at the contract ID
+    #[clap(long)]
+    force: bool,
+    /// Replace the code of the existing contract at the contract ID, keeping its data
+    #[clap(long, requires = "contract-id", conflicts_with = "force")]
+    upgrade: bool,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
+    /// Function to invoke after installing the contract, e.g. initialize. The contract is only
+    /// deployed if the function succeeds.
+    #[clap(long = "init-fn")]
+    init_fn: Option<String>,
+    /// Argument to pass to the init function
+    #[clap(
+        long = "init-arg",
+        value_name = "arg",
+        multiple = true,
+        requires = "init-fn"
+    )]
+    init_args: Vec<String>,
     /// File to persist ledger state
     #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
     ledger_file: std::path::PathBuf,
@@ -25,23 +69,297 @@ pub struct Cmd {
 pub enum Error {
     #[error("io")]
     Io(#[from] io::Error),
+    #[error("reading file {filepath}: {error}")]
+    CannotReadContractFile {
+        filepath: std::path::PathBuf,
+        error: io::Error,
+    },
+    #[error("parsing wasm: {0}")]
+    WasmParse(wasmparser::BinaryReaderError),
+    #[error("contract has no contractspecv0 section")]
+    MissingSpec,
+    #[error("contract has no interface version in its contractenvmetav0 section")]
+    MissingInterfaceVersion,
+    #[error("contract targets interface version {contract}, but the host supports {host}")]
+    InterfaceVersionMismatch { contract: u64, host: u64 },
+    #[error("contract cannot be instantiated: {0}")]
+    Instantiate(HostError),
     #[error("xdr")]
     Xdr(#[from] XdrError),
     #[error("snapshot")]
     Snapshot(#[from] snapshot::Error),
-    #[error("hex")]
-    FromHex(#[from] FromHexError),
+    #[error(transparent)]
+    ContractId(#[from] utils::ContractIdError),
+    #[error("cannot parse key {0}: expected a G... strkey or 32 hex-encoded bytes")]
+    CannotParseKey(String),
+    #[error("cannot parse salt {0}: expected 32 hex-encoded bytes")]
+    CannotParseSalt(String),
+    #[error("--salt requires --source-account or --ed25519")]
+    SaltWithoutKey,
+    #[error("contract {0} already exists, pass --force to overwrite it")]
+    ContractAlreadyExists(String),
+    #[error("contract {0} does not exist, so it cannot be upgraded")]
+    ContractNotFound(String),
+    #[error("parsing existing contract wasm: {0}")]
+    ExistingWasmParse(wasmparser::BinaryReaderError),
+    #[error("init function {0} was not found in the contract")]
+    FunctionNotFoundInContractSpec(String),
+    #[error("unexpected number of init arguments: {provided} (function {function} expects {expected} argument(s))")]
+    UnexpectedArgumentCount {
+        provided: usize,
+        expected: usize,
+        function: String,
+    },
+    #[error("parsing init argument {arg}: {error}")]
+    CannotParseArg { arg: String, error: StrValError },
+    #[error("function name {0} is too long")]
+    FunctionNameTooLong(String),
+    #[error("initializing contract: {0}")]
+    Init(HostError),
 }
 
 impl Cmd {
     pub fn run(&self) -> Result<(), Error> {
-        let contract_id: [u8; 32] = utils::contract_id_from_str(&self.contract_id)?;
-        let contract = fs::read(&self.wasm).unwrap();
+        let contract = fs::read(&self.wasm).map_err(|e| Error::CannotReadContractFile {
+            filepath: self.wasm.clone(),
+            error: e,
+        })?;
+        let contract_id = self.contract_id(&contract)?;
+        validate(contract_id, &contract)?;
 
         let mut ledger_entries = snapshot::read(&self.ledger_file)?;
-        utils::add_contract_to_ledger_entries(&mut ledger_entries, contract_id, contract)?;
+        let existing = utils::get_contract_wasm_from_ledger_entries(&ledger_entries, contract_id);
+        match existing {
+            Some(existing) if self.upgrade => report_spec_changes(&existing, &contract)?,
+            None if self.upgrade => {
+                return Err(Error::ContractNotFound(utils::contract_id_to_strkey(
+                    &contract_id,
+                )))
+            }
+            Some(_) if !self.force => {
+                return Err(Error::ContractAlreadyExists(utils::contract_id_to_strkey(
+                    &contract_id,
+                )))
+            }
+            _ => {}
+        }
+        // Only the contract code entry is replaced, so an upgraded contract keeps its data
+        utils::add_contract_to_ledger_entries(&mut ledger_entries, contract_id, contract.clone())?;
 
-        snapshot::commit(ledger_entries, [], &self.ledger_file)?;
+        // Install and initialize in one host session, and only commit if both succeed
+        if let Some(init_fn) = &self.init_fn {
+            let storage = self.initialize(contract_id, &contract, init_fn, &ledger_entries)?;
+            snapshot::commit(ledger_entries, &storage.map, &self.ledger_file)?;
+        } else {
+            snapshot::commit(ledger_entries, [], &self.ledger_file)?;
+        }
+        println!(
+            "Contract ID: {} ({})",
+            hex::encode(contract_id),
+            utils::contract_id_to_strkey(&contract_id)
+        );
         Ok(())
     }
+
+    fn initialize(
+        &self,
+        contract_id: [u8; 32],
+        contract: &[u8],
+        function: &str,
+        ledger_entries: &OrdMap<LedgerKey, LedgerEntry>,
+    ) -> Result<Storage, Error> {
+        let snap = Rc::new(snapshot::Snap {
+            ledger_entries: ledger_entries.clone(),
+        });
+        let storage = Storage::with_recording_footprint(snap);
+        let h = Host::with_storage_and_budget(storage, Budget::default());
+
+        let vm = Vm::new(&h, contract_id.into(), contract).map_err(Error::Instantiate)?;
+        let inputs = contractspec::function_spec(&vm, function)
+            .ok_or_else(|| Error::FunctionNotFoundInContractSpec(function.to_string()))?
+            .inputs;
+        if self.init_args.len() != inputs.len() {
+            return Err(Error::UnexpectedArgumentCount {
+                provided: self.init_args.len(),
+                expected: inputs.len(),
+                function: function.to_string(),
+            });
+        }
+
+        let mut complete_args = vec![
+            ScVal::Object(Some(ScObject::Bytes(contract_id.try_into()?))),
+            ScVal::Symbol(
+                function
+                    .as_bytes()
+                    .try_into()
+                    .map_err(|_| Error::FunctionNameTooLong(function.to_string()))?,
+            ),
+        ];
+        let entries = contractspec::spec_entries(&vm);
+        for (arg, input) in self.init_args.iter().zip(inputs.iter()) {
+            complete_args.push(
+                strval::from_string(arg, &input.type_, &entries).map_err(|e| {
+                    Error::CannotParseArg {
+                        arg: arg.clone(),
+                        error: e,
+                    }
+                })?,
+            );
+        }
+        h.invoke_function(HostFunction::Call, complete_args.try_into()?)
+            .map_err(Error::Init)?;
+
+        let (storage, _, _) = h.try_finish().map_err(|_h| {
+            Error::Init(HostError::from(ScStatus::HostStorageError(
+                ScHostStorageErrorCode::UnknownError,
+            )))
+        })?;
+        Ok(storage)
+    }
+
+    fn contract_id(&self, contract: &[u8]) -> Result<[u8; 32], Error> {
+        if let Some(contract_id) = &self.contract_id {
+            return Ok(utils::contract_id_from_str(
+                contract_id,
+                self.allow_short_id,
+            )?);
+        }
+        let salt = match &self.salt {
+            Some(salt) => {
+                parse_bytes32(salt).ok_or_else(|| Error::CannotParseSalt(salt.clone()))?
+            }
+            None => return Ok(utils::contract_id_from_wasm(contract)),
+        };
+        match (&self.source_account, &self.ed25519) {
+            (Some(account), _) => Ok(utils::contract_id_from_source_account(
+                parse_key(account)?,
+                salt,
+            )?),
+            (None, Some(key)) => Ok(utils::contract_id_from_ed25519(parse_key(key)?, salt)?),
+            (None, None) => Err(Error::SaltWithoutKey),
+        }
+    }
+}
+
+/// Prints the spec changes between the existing and new code of an upgraded contract, and whether
+/// each breaks existing callers.
+fn report_spec_changes(existing: &[u8], contract: &[u8]) -> Result<(), Error> {
+    let old = wasm::parse(existing)
+        .map_err(Error::ExistingWasmParse)?
+        .spec_entries()?;
+    let new = wasm::parse(contract)
+        .map_err(Error::WasmParse)?
+        .spec_entries()?;
+
+    println!("Spec changes:");
+    for c in specdiff::diff(&old, &new) {
+        let kind = if c.breaking {
+            "breaking"
+        } else {
+            "non-breaking"
+        };
+        println!(" • {}: {}: {}", kind, c.entry, c.description);
+    }
+    Ok(())
+}
+
+/// Checks that the contract will be usable once deployed, so a broken build is caught now rather
+/// than by a later invoke.
+fn validate(contract_id: [u8; 32], contract: &[u8]) -> Result<(), Error> {
+    let module = wasm::parse(contract).map_err(Error::WasmParse)?;
+
+    match module.interface_version()? {
+        Some(v) if v == meta::INTERFACE_VERSION => {}
+        Some(v) => {
+            return Err(Error::InterfaceVersionMismatch {
+                contract: v,
+                host: meta::INTERFACE_VERSION,
+            })
+        }
+        None => return Err(Error::MissingInterfaceVersion),
+    }
+
+    if module.spec.is_none() {
+        return Err(Error::MissingSpec);
+    }
+    let function_exports = module.function_exports();
+    for entry in module.spec_entries()? {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            let name = f.name.to_string_lossy();
+            if !function_exports.contains(&name.as_str()) {
+                eprintln!(
+                    "warning: function {} is in the contract spec but is not exported",
+                    name
+                );
+            }
+        }
+    }
+
+    let snap = Rc::new(snapshot::Snap {
+        ledger_entries: OrdMap::new(),
+    });
+    let h =
+        Host::with_storage_and_budget(Storage::with_recording_footprint(snap), Budget::default());
+    Vm::new(&h, contract_id.into(), contract).map_err(Error::Instantiate)?;
+    Ok(())
+}
+
+fn parse_key(key: &str) -> Result<[u8; 32], Error> {
+    if let Ok(StrkeyPublicKeyEd25519(bytes)) = StrkeyPublicKeyEd25519::from_string(key) {
+        return Ok(bytes);
+    }
+    parse_bytes32(key).ok_or_else(|| Error::CannotParseKey(key.to_string()))
+}
+
+fn parse_bytes32(s: &str) -> Option<[u8; 32]> {
+    let mut decoded = [0u8; 32];
+    hex::decode_to_slice(s, &mut decoded).ok()?;
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEX: &str = "363eaa3867841fbad0f4ed88c779e4fe66e56a2470dc98c0ec9c073d05c7b103";
+
+    #[test]
+    fn test_parse_key() {
+        let expected = parse_bytes32(HEX).unwrap();
+        assert_eq!(expected[0], 0x36);
+        assert_eq!(
+            parse_key("GA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQHES5").unwrap(),
+            expected
+        );
+        assert_eq!(parse_key(HEX).unwrap(), expected);
+        assert!(matches!(
+            parse_key(&HEX[..62]),
+            Err(Error::CannotParseKey(_))
+        ));
+        assert!(matches!(parse_key("GABC"), Err(Error::CannotParseKey(_))));
+    }
+
+    #[test]
+    fn test_parse_bytes32() {
+        assert_eq!(parse_bytes32(&"00".repeat(32)), Some([0; 32]));
+        assert_eq!(parse_bytes32(&"00".repeat(31)), None);
+        assert_eq!(parse_bytes32(&"00".repeat(33)), None);
+        assert_eq!(parse_bytes32(&"zz".repeat(32)), None);
+    }
+
+    #[test]
+    fn test_contract_ids_depend_on_key_and_salt() {
+        let key = parse_bytes32(HEX).unwrap();
+        let from_account = utils::contract_id_from_source_account(key, [0; 32]).unwrap();
+        let from_ed25519 = utils::contract_id_from_ed25519(key, [0; 32]).unwrap();
+        assert_ne!(from_account, from_ed25519);
+        assert_ne!(
+            from_account,
+            utils::contract_id_from_source_account(key, [1; 32]).unwrap()
+        );
+        assert_eq!(
+            from_account,
+            utils::contract_id_from_source_account(key, [0; 32]).unwrap()
+        );
+    }
 }
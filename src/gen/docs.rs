@@ -0,0 +1,481 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde_json::Value;
+use soroban_env_host::xdr::{
+    ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption,
+    ScSpecTypeResult, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+};
+
+use crate::{contractspec, strval};
+
+/// How user defined types are rendered in a document, as links to their definitions.
+trait Format {
+    fn link(&self, text: &str, anchor: &str) -> String;
+    /// Escapes text that is not inside a code block.
+    fn escape(&self, text: &str) -> String;
+}
+
+struct Markdown;
+
+impl Format for Markdown {
+    fn link(&self, text: &str, anchor: &str) -> String {
+        format!("[{}](#{})", text, anchor)
+    }
+
+    fn escape(&self, text: &str) -> String {
+        text.replace('<', "\\<").replace('>', "\\>")
+    }
+}
+
+struct Html;
+
+impl Format for Html {
+    fn link(&self, text: &str, anchor: &str) -> String {
+        format!("<a href=\"#{}\">{}</a>", anchor, escape_html(text))
+    }
+
+    fn escape(&self, text: &str) -> String {
+        escape_html(text)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Anchors of the definitions of the user defined types in the spec, by type name. Anchors
+/// match the ids GitHub gives headings like `### struct Config`.
+fn anchors(spec: &[ScSpecEntry]) -> BTreeMap<String, String> {
+    spec.iter()
+        .filter(|e| !matches!(e, ScSpecEntry::FunctionV0(_)))
+        .map(|e| {
+            let name = contractspec::entry_name(e);
+            let (_, udt) = name.split_once(' ').unwrap_or_default();
+            (udt.to_string(), anchor(&name))
+        })
+        .collect()
+}
+
+fn anchor(heading: &str) -> String {
+    heading.to_lowercase().replace(' ', "-")
+}
+
+/// Generates Markdown documentation of the contract's functions and types, with example
+/// invocations.
+pub fn markdown(name: &str, spec: &[ScSpecEntry]) -> String {
+    let anchors = anchors(spec);
+    let mut out = String::new();
+    writeln!(out, "# {}\n", name).unwrap();
+    out.push_str("<!-- This file was generated by soroban-cli. Do not edit. -->\n");
+
+    out.push_str("\n## Functions\n");
+    for f in functions(spec) {
+        writeln!(out, "\n### fn {}\n", f.name.to_string_lossy()).unwrap();
+        writeln!(
+            out,
+            "```rust\n{}\n```\n",
+            contractspec::function_signature(f)
+        )
+        .unwrap();
+        if !f.inputs.is_empty() {
+            out.push_str("| Argument | Type |\n| --- | --- |\n");
+            for i in f.inputs.iter() {
+                writeln!(
+                    out,
+                    "| `{}` | {} |",
+                    i.name.to_string_lossy(),
+                    type_doc(&i.type_, &anchors, &Markdown)
+                )
+                .unwrap();
+            }
+            out.push('\n');
+        }
+        writeln!(
+            out,
+            "Returns: {}\n",
+            outputs_doc(f.outputs.as_slice(), &anchors, &Markdown)
+        )
+        .unwrap();
+        if let Some(example) = example_invoke(f, spec) {
+            writeln!(out, "```sh\n{}\n```", example).unwrap();
+        }
+    }
+
+    if spec
+        .iter()
+        .any(|e| !matches!(e, ScSpecEntry::FunctionV0(_)))
+    {
+        out.push_str("\n## Types\n");
+    }
+    for entry in spec {
+        let definition = match entry {
+            ScSpecEntry::FunctionV0(_) => continue,
+            ScSpecEntry::UdtStructV0(s) => contractspec::struct_definition(s),
+            ScSpecEntry::UdtUnionV0(u) => contractspec::union_definition(u),
+        };
+        writeln!(out, "\n### {}\n", contractspec::entry_name(entry)).unwrap();
+        writeln!(out, "```rust\n{}\n```\n", definition).unwrap();
+        let (heading, members) = members(entry);
+        writeln!(out, "| {} | Type |\n| --- | --- |", heading).unwrap();
+        for (name, t) in members {
+            let t = t.map_or("-".to_string(), |t| type_doc(t, &anchors, &Markdown));
+            writeln!(out, "| `{}` | {} |", name, t).unwrap();
+        }
+    }
+    out
+}
+
+/// Generates a standalone HTML page with the same content as `markdown`.
+pub fn html(name: &str, spec: &[ScSpecEntry]) -> String {
+    let anchors = anchors(spec);
+    let title = escape_html(name);
+    let mut out = String::new();
+    out.push_str(
+        "<!DOCTYPE html>\n<!-- This file was generated by soroban-cli. Do not edit. -->\n",
+    );
+    writeln!(
+        out,
+        "<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>",
+        title, title
+    )
+    .unwrap();
+
+    out.push_str("<h2>Functions</h2>\n");
+    for f in functions(spec) {
+        let heading = format!("fn {}", f.name.to_string_lossy());
+        writeln!(
+            out,
+            "<h3 id=\"{}\">{}</h3>\n<pre><code>{}</code></pre>",
+            anchor(&heading),
+            escape_html(&heading),
+            escape_html(&contractspec::function_signature(f))
+        )
+        .unwrap();
+        if !f.inputs.is_empty() {
+            out.push_str("<table>\n<tr><th>Argument</th><th>Type</th></tr>\n");
+            for i in f.inputs.iter() {
+                writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td>{}</td></tr>",
+                    escape_html(&i.name.to_string_lossy()),
+                    type_doc(&i.type_, &anchors, &Html)
+                )
+                .unwrap();
+            }
+            out.push_str("</table>\n");
+        }
+        writeln!(
+            out,
+            "<p>Returns: {}</p>",
+            outputs_doc(f.outputs.as_slice(), &anchors, &Html)
+        )
+        .unwrap();
+        if let Some(example) = example_invoke(f, spec) {
+            writeln!(out, "<pre><code>{}</code></pre>", escape_html(&example)).unwrap();
+        }
+    }
+
+    if spec
+        .iter()
+        .any(|e| !matches!(e, ScSpecEntry::FunctionV0(_)))
+    {
+        out.push_str("<h2>Types</h2>\n");
+    }
+    for entry in spec {
+        let definition = match entry {
+            ScSpecEntry::FunctionV0(_) => continue,
+            ScSpecEntry::UdtStructV0(s) => contractspec::struct_definition(s),
+            ScSpecEntry::UdtUnionV0(u) => contractspec::union_definition(u),
+        };
+        let heading = contractspec::entry_name(entry);
+        writeln!(
+            out,
+            "<h3 id=\"{}\">{}</h3>\n<pre><code>{}</code></pre>",
+            anchor(&heading),
+            escape_html(&heading),
+            escape_html(&definition)
+        )
+        .unwrap();
+        let (heading, members) = members(entry);
+        writeln!(out, "<table>\n<tr><th>{}</th><th>Type</th></tr>", heading).unwrap();
+        for (name, t) in members {
+            let t = t.map_or("-".to_string(), |t| type_doc(t, &anchors, &Html));
+            writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td></tr>",
+                escape_html(&name),
+                t
+            )
+            .unwrap();
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn functions(spec: &[ScSpecEntry]) -> impl Iterator<Item = &ScSpecFunctionV0> {
+    spec.iter().filter_map(|e| match e {
+        ScSpecEntry::FunctionV0(f) => Some(f),
+        _ => None,
+    })
+}
+
+/// The fields of a struct or the cases of a union, with their types.
+fn members(entry: &ScSpecEntry) -> (&'static str, Vec<(String, Option<&ScSpecTypeDef>)>) {
+    match entry {
+        ScSpecEntry::UdtStructV0(s) => (
+            "Field",
+            s.fields
+                .iter()
+                .map(|f| (f.name.to_string_lossy(), Some(&f.type_)))
+                .collect(),
+        ),
+        ScSpecEntry::UdtUnionV0(u) => (
+            "Variant",
+            u.cases
+                .iter()
+                .map(|c| (c.name.to_string_lossy(), c.type_.as_ref()))
+                .collect(),
+        ),
+        ScSpecEntry::FunctionV0(_) => ("", vec![]),
+    }
+}
+
+fn outputs_doc(
+    outputs: &[ScSpecTypeDef],
+    anchors: &BTreeMap<String, String>,
+    format: &dyn Format,
+) -> String {
+    match outputs {
+        [t] => type_doc(t, anchors, format),
+        _ => {
+            let types = outputs
+                .iter()
+                .map(|t| type_doc(t, anchors, format))
+                .collect::<Vec<_>>();
+            format!("({})", types.join(", "))
+        }
+    }
+}
+
+/// Renders a type like `contractspec::type_to_string`, with user defined types linked to their
+/// definitions.
+fn type_doc(t: &ScSpecTypeDef, anchors: &BTreeMap<String, String>, format: &dyn Format) -> String {
+    let generic = |name: &str, params: Vec<String>| {
+        format!(
+            "{}{}{}{}",
+            name,
+            format.escape("<"),
+            params.join(", "),
+            format.escape(">")
+        )
+    };
+    match t {
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            generic("Option", vec![type_doc(value_type, anchors, format)])
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult {
+                ok_type,
+                error_type,
+            } = &**r;
+            generic(
+                "Result",
+                vec![
+                    type_doc(ok_type, anchors, format),
+                    type_doc(error_type, anchors, format),
+                ],
+            )
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            generic("Vec", vec![type_doc(element_type, anchors, format)])
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            generic("Set", vec![type_doc(element_type, anchors, format)])
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            generic(
+                "Map",
+                vec![
+                    type_doc(key_type, anchors, format),
+                    type_doc(value_type, anchors, format),
+                ],
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            outputs_doc(value_types.as_slice(), anchors, format)
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            let name = name.to_string_lossy();
+            match anchors.get(&name) {
+                Some(anchor) => format.link(&name, anchor),
+                None => format.escape(&name),
+            }
+        }
+        _ => contractspec::type_to_string(t),
+    }
+}
+
+/// An invoke command line for the function, with placeholder arguments of the right types. There
+/// is none if an argument has a type that `invoke --arg` can't parse, such as a `Status`.
+fn example_invoke(f: &ScSpecFunctionV0, spec: &[ScSpecEntry]) -> Option<String> {
+    let mut command = format!(
+        "soroban-cli invoke --id <CONTRACT_ID> --fn {}",
+        f.name.to_string_lossy()
+    );
+    for i in f.inputs.iter() {
+        write!(
+            command,
+            " --arg {}",
+            shell_quote(&example_arg(&i.type_, spec)?)
+        )
+        .unwrap();
+    }
+    Some(command)
+}
+
+/// A placeholder `--arg` value of the type.
+fn example_arg(t: &ScSpecTypeDef, spec: &[ScSpecEntry]) -> Option<String> {
+    // Symbols and BigInts are read without JSON quotes at the top level
+    Some(match (t, example_value(t, spec)?) {
+        (ScSpecTypeDef::Symbol | ScSpecTypeDef::BigInt, Value::String(s)) => s,
+        (_, v) => v.to_string(),
+    })
+}
+
+/// A placeholder value of the type, in the JSON form `strval` reads. There is none for types
+/// `strval` can't read.
+fn example_value(t: &ScSpecTypeDef, spec: &[ScSpecEntry]) -> Option<Value> {
+    let v = contractspec::example_value(t, spec)?;
+    strval::to_json_of_type(&v, t, spec).ok()
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,:".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_env_host::xdr::{
+        ScSpecFunctionInputV0, ScSpecUdtStructFieldV0, ScSpecUdtStructV0, ScSpecUdtUnionCaseV0,
+        ScSpecUdtUnionV0,
+    };
+
+    fn udt(name: &str) -> ScSpecTypeDef {
+        ScSpecTypeDef::Udt(ScSpecTypeUdt {
+            name: name.as_bytes().try_into().unwrap(),
+        })
+    }
+
+    fn option(t: ScSpecTypeDef) -> ScSpecTypeDef {
+        ScSpecTypeDef::Option(Box::new(ScSpecTypeOption { value_type: t }))
+    }
+
+    fn function(name: &str, inputs: Vec<ScSpecTypeDef>) -> ScSpecFunctionV0 {
+        ScSpecFunctionV0 {
+            name: name.as_bytes().try_into().unwrap(),
+            inputs: inputs
+                .into_iter()
+                .enumerate()
+                .map(|(i, type_)| ScSpecFunctionInputV0 {
+                    name: format!("a{}", i).as_bytes().try_into().unwrap(),
+                    type_,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            outputs: vec![].try_into().unwrap(),
+        }
+    }
+
+    fn spec() -> Vec<ScSpecEntry> {
+        vec![
+            // A recursive struct, which ends at the Option
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                name: "Node".as_bytes().try_into().unwrap(),
+                fields: vec![
+                    ScSpecUdtStructFieldV0 {
+                        name: "value".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::Bitset,
+                    },
+                    ScSpecUdtStructFieldV0 {
+                        name: "next".as_bytes().try_into().unwrap(),
+                        type_: option(udt("Node")),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+                name: "Key".as_bytes().try_into().unwrap(),
+                cases: vec![ScSpecUdtUnionCaseV0 {
+                    name: "Balance".as_bytes().try_into().unwrap(),
+                    type_: Some(ScSpecTypeDef::Symbol),
+                }]
+                .try_into()
+                .unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_example_args_parse() {
+        let spec = spec();
+        let types = vec![
+            udt("Node"),
+            udt("Key"),
+            option(ScSpecTypeDef::U32),
+            ScSpecTypeDef::Set(Box::new(ScSpecTypeSet {
+                element_type: ScSpecTypeDef::U32,
+            })),
+            ScSpecTypeDef::Bitset,
+            ScSpecTypeDef::Symbol,
+            ScSpecTypeDef::BigInt,
+            ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+                key_type: ScSpecTypeDef::U32,
+                value_type: udt("Key"),
+            })),
+        ];
+        for t in &types {
+            let arg = example_arg(t, &spec).unwrap();
+            let res = strval::from_string(&arg, t, &spec);
+            assert!(res.is_ok(), "{:?} {}: {:?}", t, arg, res);
+        }
+        assert!(example_invoke(&function("f", types), &spec).is_some());
+    }
+
+    #[test]
+    fn test_no_example_for_unparseable_args() {
+        let spec = spec();
+        assert_eq!(example_arg(&ScSpecTypeDef::Status, &spec), None);
+        assert_eq!(example_arg(&udt("Missing"), &spec), None);
+        assert_eq!(
+            example_invoke(
+                &function("f", vec![ScSpecTypeDef::U32, ScSpecTypeDef::Status]),
+                &spec
+            ),
+            None
+        );
+    }
+}
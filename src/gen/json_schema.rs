@@ -0,0 +1,300 @@
+use serde_json::{json, Map, Value};
+use soroban_env_host::xdr::{
+    ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeSet,
+    ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec, ScSpecUdtStructV0, ScSpecUdtUnionV0,
+};
+
+use crate::contractspec;
+
+/// Generates a JSON Schema per function, keyed by function name, describing an object of the
+/// function's arguments. Each property is a value `strval::from_json` accepts for that argument,
+/// the same as `invoke --arg` takes. Each schema is standalone, and carries the definitions of all
+/// the user defined types in the spec.
+pub fn generate(spec: &[ScSpecEntry]) -> Value {
+    let mut definitions = Map::new();
+    for entry in spec {
+        match entry {
+            ScSpecEntry::UdtStructV0(s) => {
+                definitions.insert(s.name.to_string_lossy(), struct_schema(s));
+            }
+            ScSpecEntry::UdtUnionV0(u) => {
+                definitions.insert(u.name.to_string_lossy(), union_schema(u));
+            }
+            ScSpecEntry::FunctionV0(_) => {}
+        }
+    }
+
+    let mut functions = Map::new();
+    for entry in spec {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            let mut schema = function_schema(f);
+            schema["definitions"] = Value::Object(definitions.clone());
+            functions.insert(f.name.to_string_lossy(), schema);
+        }
+    }
+    Value::Object(functions)
+}
+
+fn function_schema(f: &ScSpecFunctionV0) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    // Every argument is required, because invoke takes them all positionally, with null for None
+    for i in f.inputs.iter() {
+        let name = i.name.to_string_lossy();
+        properties.insert(name.clone(), type_schema(&i.type_));
+        required.push(name);
+    }
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": f.name.to_string_lossy(),
+        "description": contractspec::function_signature(f),
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn struct_schema(s: &ScSpecUdtStructV0) -> Value {
+    let mut properties = Map::new();
+    for f in s.fields.iter() {
+        properties.insert(f.name.to_string_lossy(), type_schema(&f.type_));
+    }
+    let required = s
+        .fields
+        .iter()
+        .map(|f| f.name.to_string_lossy())
+        .collect::<Vec<_>>();
+    json!({
+        "description": contractspec::struct_definition(s),
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Cases without a value are written as the case name, and cases with a value as an object with
+/// the case name as its only key, e.g. `"Admin"` or `{ "Balance": "GA..." }`.
+fn union_schema(u: &ScSpecUdtUnionV0) -> Value {
+    let cases = u
+        .cases
+        .iter()
+        .map(|c| {
+            let name = c.name.to_string_lossy();
+            match &c.type_ {
+                Some(t) => json!({
+                    "type": "object",
+                    "properties": { name.clone(): type_schema(t) },
+                    "required": [name],
+                    "additionalProperties": false,
+                }),
+                None => json!({ "const": name }),
+            }
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "description": contractspec::union_definition(u),
+        "oneOf": cases,
+    })
+}
+
+/// The schema of the JSON value `strval::from_json` accepts for a type.
+fn type_schema(t: &ScSpecTypeDef) -> Value {
+    match t {
+        ScSpecTypeDef::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+        ScSpecTypeDef::I32 => {
+            json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX })
+        }
+        // U64 values are stored as a U63, so the top bit is not available
+        ScSpecTypeDef::U64 => json!({ "type": "integer", "minimum": 0, "maximum": i64::MAX }),
+        ScSpecTypeDef::I64 => {
+            json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX })
+        }
+        // BigInts are usually larger than JSON numbers can represent exactly
+        ScSpecTypeDef::BigInt => json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+        ScSpecTypeDef::Bitset => json!({ "type": "integer", "minimum": 0 }),
+        ScSpecTypeDef::Bool => json!({ "type": "boolean" }),
+        ScSpecTypeDef::Symbol => json!({
+            "type": "string",
+            "maxLength": 10,
+            "pattern": "^[a-zA-Z0-9_]*$",
+        }),
+        ScSpecTypeDef::Bytes => json!({
+            "anyOf": [
+                { "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } },
+                { "type": "string" },
+            ]
+        }),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            json!({ "anyOf": [{ "type": "null" }, type_schema(value_type)] })
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            json!({ "type": "array", "items": type_schema(element_type) })
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            json!({
+                "type": "array",
+                "items": type_schema(element_type),
+                "uniqueItems": true,
+            })
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            json!({
+                "type": "array",
+                "items": value_types.iter().map(type_schema).collect::<Vec<_>>(),
+                "minItems": value_types.len(),
+                "maxItems": value_types.len(),
+            })
+        }
+        // Map keys are parsed from the JSON object's keys with `strval::from_string`
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            json!({
+                "type": "object",
+                "description": format!("keys are {}", contractspec::type_to_string(key_type)),
+                "additionalProperties": type_schema(value_type),
+            })
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            json!({ "$ref": format!("#/definitions/{}", name.to_string_lossy()) })
+        }
+        // Nothing is accepted for types that can't be written as JSON
+        ScSpecTypeDef::Status | ScSpecTypeDef::Result(_) => json!({
+            "not": {},
+            "description": format!("{} values can't be passed as JSON", contractspec::type_to_string(t)),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strval;
+    use soroban_env_host::xdr::{
+        ScSpecFunctionInputV0, ScSpecUdtStructFieldV0, ScSpecUdtUnionCaseV0, ScVal,
+    };
+
+    fn spec() -> Vec<ScSpecEntry> {
+        let udt = |name: &str| {
+            ScSpecTypeDef::Udt(ScSpecTypeUdt {
+                name: name.as_bytes().try_into().unwrap(),
+            })
+        };
+        vec![
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                name: "Config".as_bytes().try_into().unwrap(),
+                fields: vec![
+                    ScSpecUdtStructFieldV0 {
+                        name: "admin".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::Symbol,
+                    },
+                    ScSpecUdtStructFieldV0 {
+                        name: "limit".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                            value_type: ScSpecTypeDef::U32,
+                        })),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+                name: "Key".as_bytes().try_into().unwrap(),
+                cases: vec![
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Admin".as_bytes().try_into().unwrap(),
+                        type_: None,
+                    },
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Balance".as_bytes().try_into().unwrap(),
+                        type_: Some(ScSpecTypeDef::U32),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+                name: "set".as_bytes().try_into().unwrap(),
+                inputs: [
+                    ("config", udt("Config")),
+                    ("key", udt("Key")),
+                    ("flags", ScSpecTypeDef::Bitset),
+                    (
+                        "ids",
+                        ScSpecTypeDef::Set(Box::new(ScSpecTypeSet {
+                            element_type: ScSpecTypeDef::U32,
+                        })),
+                    ),
+                    (
+                        "max",
+                        ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                            value_type: ScSpecTypeDef::U32,
+                        })),
+                    ),
+                ]
+                .into_iter()
+                .map(|(name, type_)| ScSpecFunctionInputV0 {
+                    name: name.as_bytes().try_into().unwrap(),
+                    type_,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+                outputs: vec![].try_into().unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_every_argument_is_required() {
+        let schema = generate(&spec());
+        assert_eq!(
+            schema["set"]["required"],
+            json!(["config", "key", "flags", "ids", "max"])
+        );
+    }
+
+    #[test]
+    fn test_schema_valid_values_parse() {
+        let spec = spec();
+        let f = match &spec[2] {
+            ScSpecEntry::FunctionV0(f) => f,
+            _ => unreachable!(),
+        };
+        let documents = [
+            json!({
+                "config": { "admin": "me", "limit": 5 },
+                "key": { "Balance": 1 },
+                "flags": 3,
+                "ids": [1, 2],
+                "max": 10,
+            }),
+            json!({
+                "config": { "admin": "me", "limit": null },
+                "key": "Admin",
+                "flags": 0,
+                "ids": [],
+                "max": null,
+            }),
+        ];
+        for doc in &documents {
+            for i in f.inputs.iter() {
+                let name = i.name.to_string_lossy();
+                let res = strval::from_json(&doc[&name], &i.type_, &spec);
+                assert!(res.is_ok(), "{} = {}: {:?}", name, doc[&name], res);
+            }
+        }
+        assert_eq!(
+            strval::from_json(&json!(3), &ScSpecTypeDef::Bitset, &spec).unwrap(),
+            ScVal::Bitset(3)
+        );
+    }
+}
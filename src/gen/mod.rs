@@ -0,0 +1,926 @@
+use std::{collections::BTreeMap, fmt::Debug, fs, io, path::Path, path::PathBuf};
+
+use clap::{ArgEnum, Parser};
+use sha2::{Digest, Sha256};
+use soroban_env_host::xdr::{
+    self, ScSpecEntry, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeResult,
+    ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+};
+use soroban_spec::{
+    gen::{json, rust},
+    read::{from_wasm, FromWasmError},
+};
+
+use crate::contractspec;
+use crate::snapshot;
+use crate::spec;
+use crate::utils;
+use crate::wasm;
+
+mod docs;
+mod json_schema;
+mod python;
+mod rust_test;
+mod typescript;
+
+const SOROBAN_SDK_VERSION: &str = "0.1.0";
+const SOROBAN_ENV_HOST_VERSION: &str = "0.0.3";
+const SOROBAN_CLIENT_VERSION: &str = "0.1.0";
+const STELLAR_SDK_VERSION: &str = "8.1.0";
+/// The host source the CLI builds against, the same as the patch in its Cargo.toml, so generated
+/// tests run on the same host.
+const SOROBAN_ENV_HOST_PATCH: &str = r#"[patch.crates-io]
+soroban-env-common = { git = "https://github.com/stellar/rs-soroban-env", rev = "0e6cb7aa" }
+soroban-env-host = { git = "https://github.com/stellar/rs-soroban-env", rev = "0e6cb7aa" }
+soroban-env-macros = { git = "https://github.com/stellar/rs-soroban-env", rev = "0e6cb7aa" }
+soroban-native-sdk-macros = { git = "https://github.com/stellar/rs-soroban-env", rev = "0e6cb7aa" }
+stellar-xdr = { git = "https://github.com/stellar/rs-stellar-xdr", rev = "8c8d334" }
+"#;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// WASM file to generate bindings for, can be repeated
+    #[clap(
+        long,
+        parse(from_os_str),
+        multiple_occurrences = true,
+        required_unless_present_any = &["contract-id", "spec-xdr", "spec-json"]
+    )]
+    wasm: Vec<PathBuf>,
+    /// Contract ID to read the WASM from, instead of a file, can be repeated. Rust output for a
+    /// contract ID requires --out-dir, so the WASM it imports is written with it.
+    #[clap(long = "id", multiple_occurrences = true)]
+    contract_id: Vec<String>,
+    /// Contract spec to generate bindings from, instead of a WASM file. Either base64, such as
+    /// the Contract Spec printed by inspect, or a file containing the spec as xdr or base64.
+    #[clap(long)]
+    spec_xdr: Option<String>,
+    /// File containing a contract spec as json, as written by spec extract --format json, to
+    /// generate bindings from instead of a WASM file
+    #[clap(long, parse(from_os_str))]
+    spec_json: Option<PathBuf>,
+    /// File to read ledger state from, when using --id. Rust tests of a --wasm contract that is
+    /// deployed in it use its ID there.
+    #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
+    ledger_file: PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
+    /// Type of output to generate
+    #[clap(long, arg_enum)]
+    r#output: Output,
+    /// Directory to write a package containing the bindings to, instead of printing them.
+    /// Required when generating bindings for more than one contract. The package has the
+    /// version in the contract meta, or 0.0.0+<hash of the code> if it has none.
+    #[clap(long, parse(from_os_str))]
+    out_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Output {
+    /// Rust trait, client bindings, and test harness
+    Rust,
+    /// Rust test module that invokes each function on a host loaded with the ledger file, ignored
+    /// until the placeholder arguments are replaced
+    RustTest,
+    /// Json representation of contract spec types
+    Json,
+    /// Json Schema of the arguments of each function, in the form invoke --arg accepts them
+    JsonSchema,
+    /// TypeScript types and a client that calls the contract over JSON-RPC
+    Typescript,
+    /// Python dataclasses and a client that calls the contract over JSON-RPC
+    Python,
+    /// Markdown documentation of the contract's functions and types
+    Markdown,
+    /// Html documentation of the contract's functions and types
+    Html,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("reading contract spec of {name}: {error}")]
+    FromWasm { name: String, error: FromWasmError },
+    #[error("format rust error: {0}")]
+    FormatRust(syn::Error),
+    #[error("writing file {filepath}: {error}")]
+    CannotWriteFile { filepath: PathBuf, error: io::Error },
+    #[error("xdr")]
+    Xdr(#[from] xdr::Error),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
+    #[error("--out-dir is required when generating bindings for more than one contract")]
+    OutDirRequired,
+    #[error(
+        "--out-dir is required for rust output with --id, to write {0} which the code imports"
+    )]
+    OutDirRequiredForId(String),
+    #[error("reading spec file {filepath}: {error}")]
+    CannotReadSpecFile { filepath: PathBuf, error: io::Error },
+    #[error("spec is neither a file nor base64: {0}")]
+    InvalidSpecBase64(base64::DecodeError),
+    #[error("parsing spec file {filepath}: {error}")]
+    SpecJson {
+        filepath: PathBuf,
+        error: serde_json::Error,
+    },
+    #[error(
+        "--out-dir is required for rust-test output, to write the WASM and ledger the tests load"
+    )]
+    OutDirRequiredForRustTest,
+    #[error("rust-test output needs the WASM or ID of contract {0}, not only its spec")]
+    MissingContractId(String),
+    #[error("more than one contract is named {0}")]
+    DuplicateName(String),
+    #[error("{0:?} has no characters that can be used in a package name")]
+    InvalidPackageName(String),
+}
+
+/// A contract to generate bindings for.
+struct Contract {
+    /// Name of the contract's package or module
+    name: String,
+    /// File the generated Rust code imports the contract from, when printing
+    file: String,
+    /// ID the contract is deployed at, or would be by `deploy` without --id. Unknown when
+    /// generating from a spec.
+    contract_id: Option<[u8; 32]>,
+    /// Whether the contract was read from a file, rather than from the ledger or a spec
+    from_file: bool,
+    /// WASM of the contract, unless generating from a spec
+    contents: Option<Vec<u8>>,
+    spec: Vec<ScSpecEntry>,
+}
+
+impl Contract {
+    fn from_spec(name: String, spec: Vec<ScSpecEntry>) -> Self {
+        Contract {
+            file: format!("{}.wasm", name),
+            name,
+            contract_id: None,
+            from_file: false,
+            contents: None,
+            spec,
+        }
+    }
+}
+
+/// Name and version of the package the bindings are written to.
+struct Package {
+    name: String,
+    version: String,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contracts = self.contracts()?;
+        match (&self.out_dir, contracts.as_slice()) {
+            (None, [contract]) => {
+                // The WASM from the ledger isn't on disk for the printed code to import
+                if matches!(self.output, Output::Rust)
+                    && !contract.from_file
+                    && contract.contents.is_some()
+                {
+                    return Err(Error::OutDirRequiredForId(contract.file.clone()));
+                }
+                let code = match self.output {
+                    Output::Rust => Self::generate_rust(
+                        &contract.file,
+                        contract.contents.as_deref(),
+                        &contract.spec,
+                    )?,
+                    Output::RustTest => return Err(Error::OutDirRequiredForRustTest),
+                    Output::Json => Self::generate_json(&contract.spec),
+                    Output::JsonSchema => Self::generate_json_schema(&contract.spec)?,
+                    Output::Typescript => Self::generate_typescript(&contract.spec),
+                    Output::Python => Self::generate_python(&contract.spec),
+                    Output::Markdown => docs::markdown(&contract.name, &contract.spec),
+                    Output::Html => docs::html(&contract.name, &contract.spec),
+                };
+                print!("{}", code);
+                Ok(())
+            }
+            (None, _) => Err(Error::OutDirRequired),
+            (Some(out_dir), [contract]) => self.write_package(out_dir, contract),
+            (Some(out_dir), _) => self.write_workspace(out_dir, &contracts),
+        }
+    }
+
+    /// Reads the contracts given by --wasm, followed by those given by --id, --spec-xdr and
+    /// --spec-json.
+    fn contracts(&self) -> Result<Vec<Contract>, Error> {
+        let read_spec = |name: &str, contents: &[u8]| {
+            from_wasm(contents).map_err(|e| Error::FromWasm {
+                name: name.to_string(),
+                error: e,
+            })
+        };
+
+        // A contract deployed to the ledger is tested at its ID there, others at the ID deploy
+        // would give them
+        let ledger_entries = snapshot::read(&self.ledger_file).unwrap_or_default();
+
+        let mut contracts = vec![];
+        for wasm in &self.wasm {
+            let contents = fs::read(wasm).map_err(|e| wasm::Error::CannotReadContractFile {
+                filepath: wasm.clone(),
+                error: e,
+            })?;
+            let name = file_stem(wasm);
+            contracts.push(Contract {
+                spec: read_spec(&name, &contents)?,
+                name,
+                file: wasm.to_string_lossy().to_string(),
+                contract_id: Some(
+                    utils::find_contract_id_with_wasm(&ledger_entries, &contents)
+                        .unwrap_or_else(|| utils::contract_id_from_wasm(&contents)),
+                ),
+                from_file: true,
+                contents: Some(contents),
+            });
+        }
+        for contract_id in &self.contract_id {
+            let contents = wasm::from_ledger(&self.ledger_file, contract_id, self.allow_short_id)?;
+            let id = utils::contract_id_from_str(contract_id, self.allow_short_id)
+                .map_err(wasm::Error::from)?;
+            let name = format!("contract_{}", contract_id);
+            contracts.push(Contract {
+                spec: read_spec(&name, &contents)?,
+                name,
+                file: format!("{}.wasm", contract_id),
+                contract_id: Some(id),
+                from_file: false,
+                contents: Some(contents),
+            });
+        }
+        if let Some(spec_xdr) = &self.spec_xdr {
+            let (name, spec) = spec_from_xdr(spec_xdr)?;
+            contracts.push(Contract::from_spec(name, spec));
+        }
+        if let Some(spec_json) = &self.spec_json {
+            let contents = fs::read(spec_json).map_err(|e| Error::CannotReadSpecFile {
+                filepath: spec_json.clone(),
+                error: e,
+            })?;
+            let spec = serde_json::from_slice(&contents).map_err(|e| Error::SpecJson {
+                filepath: spec_json.clone(),
+                error: e,
+            })?;
+            contracts.push(Contract::from_spec(file_stem(spec_json), spec));
+        }
+
+        let mut names = vec![];
+        for contract in &contracts {
+            if names.contains(&contract.name) {
+                return Err(Error::DuplicateName(contract.name.clone()));
+            }
+            names.push(contract.name.clone());
+        }
+        Ok(contracts)
+    }
+
+    /// Writes the bindings for a single contract as a package named after it.
+    fn write_package(&self, out_dir: &Path, contract: &Contract) -> Result<(), Error> {
+        let package = Package {
+            name: package_name(&contract.name)?,
+            version: version(&[contract]),
+        };
+        match self.output {
+            Output::Rust => {
+                // The code is copied into the crate so that it builds on its own
+                let wasm_file = format!("{}.wasm", package.name);
+                let code =
+                    Self::generate_rust(&wasm_file, contract.contents.as_deref(), &contract.spec)?;
+                write_file(&out_dir.join("Cargo.toml"), cargo_toml(&package).as_bytes())?;
+                if let Some(contents) = &contract.contents {
+                    write_file(&out_dir.join(wasm_file), contents)?;
+                }
+                write_file(&out_dir.join("src").join("lib.rs"), code.as_bytes())?;
+            }
+            Output::RustTest => {
+                self.write_rust_tests(out_dir, &package, &[contract])?;
+            }
+            Output::Json => {
+                let code = Self::generate_json(&contract.spec);
+                write_file(
+                    &out_dir.join(format!("{}.json", package.name)),
+                    code.as_bytes(),
+                )?;
+            }
+            Output::JsonSchema => {
+                let code = Self::generate_json_schema(&contract.spec)?;
+                write_file(
+                    &out_dir.join(format!("{}.schema.json", package.name)),
+                    code.as_bytes(),
+                )?;
+            }
+            Output::Typescript => {
+                write_file(
+                    &out_dir.join("package.json"),
+                    package_json(&package).as_bytes(),
+                )?;
+                write_file(
+                    &out_dir.join("index.ts"),
+                    Self::generate_typescript(&contract.spec).as_bytes(),
+                )?;
+            }
+            Output::Python => {
+                write_file(
+                    &out_dir.join("pyproject.toml"),
+                    pyproject_toml(&package).as_bytes(),
+                )?;
+                write_file(
+                    &out_dir.join(module_name(&package.name)).join("__init__.py"),
+                    Self::generate_python(&contract.spec).as_bytes(),
+                )?;
+            }
+            Output::Markdown => {
+                write_file(
+                    &out_dir.join(format!("{}.md", package.name)),
+                    docs::markdown(&contract.name, &contract.spec).as_bytes(),
+                )?;
+            }
+            Output::Html => {
+                write_file(
+                    &out_dir.join(format!("{}.html", package.name)),
+                    docs::html(&contract.name, &contract.spec).as_bytes(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the bindings for several contracts as a package named after the directory, with
+    /// one module per contract and a `types` module holding the types they share.
+    fn write_workspace(&self, out_dir: &Path, contracts: &[Contract]) -> Result<(), Error> {
+        let package = Package {
+            name: package_name(
+                &out_dir
+                    .canonicalize()
+                    .unwrap_or_else(|_| out_dir.to_path_buf())
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "contracts".to_string()),
+            )?,
+            version: version(&contracts.iter().collect::<Vec<_>>()),
+        };
+        let shared = shared_types(contracts);
+        // Each contract's own entries, and the shared types it uses
+        let split = |contract: &Contract| -> (Vec<ScSpecEntry>, Vec<ScSpecEntry>) {
+            contract
+                .spec
+                .iter()
+                .cloned()
+                .partition(|e| !shared.contains(e))
+        };
+
+        match self.output {
+            Output::Rust => {
+                let mut code = format!("pub mod types {{ {} }}", rust_types(&shared));
+                for contract in contracts {
+                    let wasm_file = format!("{}.wasm", contract.name);
+                    let (own, _) = split(contract);
+                    let generated = match &contract.contents {
+                        Some(contents) => {
+                            write_file(&out_dir.join(&wasm_file), contents)?;
+                            let sha256 = format!("{:x}", Sha256::digest(contents));
+                            rust::generate(&own, &wasm_file, &sha256)
+                        }
+                        None => rust::generate_without_file(&own),
+                    };
+                    code.push_str(&format!(
+                        "pub mod {} {{ #[allow(unused_imports)] use super::types::*; {} }}",
+                        module_name(&contract.name),
+                        generated
+                    ));
+                }
+                write_file(&out_dir.join("Cargo.toml"), cargo_toml(&package).as_bytes())?;
+                write_file(
+                    &out_dir.join("src").join("lib.rs"),
+                    format_rust(&code)?.as_bytes(),
+                )?;
+            }
+            Output::RustTest => {
+                self.write_rust_tests(out_dir, &package, &contracts.iter().collect::<Vec<_>>())?;
+            }
+            Output::Json => {
+                write_file(
+                    &out_dir.join("types.json"),
+                    Self::generate_json(&shared).as_bytes(),
+                )?;
+                for contract in contracts {
+                    let (own, _) = split(contract);
+                    write_file(
+                        &out_dir.join(format!("{}.json", contract.name)),
+                        Self::generate_json(&own).as_bytes(),
+                    )?;
+                }
+            }
+            // Schemas are standalone documents, so shared types aren't factored out
+            Output::JsonSchema => {
+                for contract in contracts {
+                    write_file(
+                        &out_dir.join(format!("{}.schema.json", contract.name)),
+                        Self::generate_json_schema(&contract.spec)?.as_bytes(),
+                    )?;
+                }
+            }
+            Output::Typescript => {
+                let mut index = String::new();
+                write_file(
+                    &out_dir.join("types.ts"),
+                    typescript::generate_shared(&shared).as_bytes(),
+                )?;
+                for contract in contracts {
+                    let (own, used) = split(contract);
+                    let module = module_name(&contract.name);
+                    write_file(
+                        &out_dir.join(format!("{}.ts", module)),
+                        typescript::generate_contract(&own, &used).as_bytes(),
+                    )?;
+                    index.push_str(&format!("export * as {} from \"./{}\";\n", module, module));
+                }
+                write_file(
+                    &out_dir.join("package.json"),
+                    package_json(&package).as_bytes(),
+                )?;
+                write_file(&out_dir.join("index.ts"), index.as_bytes())?;
+            }
+            Output::Python => {
+                let dir = out_dir.join(module_name(&package.name));
+                let mut modules = vec![];
+                write_file(
+                    &dir.join("types.py"),
+                    python::generate_shared(&shared).as_bytes(),
+                )?;
+                for contract in contracts {
+                    let (own, used) = split(contract);
+                    let module = module_name(&contract.name);
+                    write_file(
+                        &dir.join(format!("{}.py", module)),
+                        python::generate_contract(&own, &used).as_bytes(),
+                    )?;
+                    modules.push(module);
+                }
+                write_file(
+                    &out_dir.join("pyproject.toml"),
+                    pyproject_toml(&package).as_bytes(),
+                )?;
+                write_file(
+                    &dir.join("__init__.py"),
+                    format!("from . import {}\n", modules.join(", ")).as_bytes(),
+                )?;
+            }
+            // Each page documents every type the contract uses, so types aren't factored out
+            Output::Markdown => {
+                let mut index = format!("# {}\n\n", package.name);
+                for contract in contracts {
+                    let file = format!("{}.md", contract.name);
+                    write_file(
+                        &out_dir.join(&file),
+                        docs::markdown(&contract.name, &contract.spec).as_bytes(),
+                    )?;
+                    index.push_str(&format!("- [{}]({})\n", contract.name, file));
+                }
+                write_file(&out_dir.join("README.md"), index.as_bytes())?;
+            }
+            Output::Html => {
+                let mut index = format!(
+                    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n<ul>\n",
+                    package.name, package.name
+                );
+                for contract in contracts {
+                    let file = format!("{}.html", contract.name);
+                    write_file(
+                        &out_dir.join(&file),
+                        docs::html(&contract.name, &contract.spec).as_bytes(),
+                    )?;
+                    index.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a></li>\n",
+                        file, contract.name
+                    ));
+                }
+                index.push_str("</ul>\n</body>\n</html>\n");
+                write_file(&out_dir.join("index.html"), index.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a crate with a test file per contract. The ledger file is copied into the crate as
+    /// a fixture, so the tests don't change when the sandbox does.
+    fn write_rust_tests(
+        &self,
+        out_dir: &Path,
+        package: &Package,
+        contracts: &[&Contract],
+    ) -> Result<(), Error> {
+        let ledger_file = match fs::read(&self.ledger_file) {
+            Ok(ledger) => {
+                let file = "tests/fixtures/ledger.json";
+                write_file(&out_dir.join(file), &ledger)?;
+                Some(file)
+            }
+            Err(_) => None,
+        };
+        for contract in contracts {
+            let wasm_file = format!("{}.wasm", contract.name);
+            let code = Self::generate_rust_test(
+                contract,
+                contract.from_file.then(|| wasm_file.as_str()),
+                ledger_file,
+            )?;
+            if let (true, Some(contents)) = (contract.from_file, &contract.contents) {
+                write_file(&out_dir.join(&wasm_file), contents)?;
+            }
+            write_file(
+                &out_dir
+                    .join("tests")
+                    .join(format!("{}.rs", module_name(&contract.name))),
+                code.as_bytes(),
+            )?;
+        }
+        write_file(
+            &out_dir.join("Cargo.toml"),
+            cargo_toml_test(package).as_bytes(),
+        )?;
+        write_file(
+            &out_dir.join("src").join("lib.rs"),
+            b"// The tests are in the tests directory.\n",
+        )?;
+        Ok(())
+    }
+
+    pub fn generate_rust(
+        file: &str,
+        contents: Option<&[u8]>,
+        spec: &[ScSpecEntry],
+    ) -> Result<String, Error> {
+        // Without the WASM there's nothing for the generated code to import
+        let code = match contents {
+            Some(contents) => {
+                let sha256 = format!("{:x}", Sha256::digest(contents));
+                rust::generate(spec, file, &sha256)
+            }
+            None => rust::generate_without_file(spec),
+        };
+        format_rust(&code.to_string())
+    }
+
+    fn generate_rust_test(
+        contract: &Contract,
+        wasm_file: Option<&str>,
+        ledger_file: Option<&str>,
+    ) -> Result<String, Error> {
+        let contract_id = contract
+            .contract_id
+            .ok_or_else(|| Error::MissingContractId(contract.name.clone()))?;
+        let code = rust_test::generate(&contract.spec, contract_id, wasm_file, ledger_file)?;
+        format_rust(&code)
+    }
+
+    pub fn generate_json(spec: &[ScSpecEntry]) -> String {
+        format!("{}\n", json::generate(spec))
+    }
+
+    pub fn generate_json_schema(spec: &[ScSpecEntry]) -> Result<String, Error> {
+        let schema = json_schema::generate(spec);
+        Ok(format!("{}\n", serde_json::to_string_pretty(&schema)?))
+    }
+
+    pub fn generate_typescript(spec: &[ScSpecEntry]) -> String {
+        typescript::generate(spec)
+    }
+
+    pub fn generate_python(spec: &[ScSpecEntry]) -> String {
+        python::generate(spec)
+    }
+}
+
+fn format_rust(code_raw: &str) -> Result<String, Error> {
+    match syn::parse_file(code_raw) {
+        Ok(file) => Ok(prettyplease::unparse(&file)),
+        Err(e) => {
+            println!("{}", code_raw);
+            Err(Error::FormatRust(e))
+        }
+    }
+}
+
+/// Rust definitions of the user defined types in the spec.
+fn rust_types(spec: &[ScSpecEntry]) -> String {
+    spec.iter()
+        .filter_map(|e| match e {
+            ScSpecEntry::UdtStructV0(s) => Some(rust::r#type::generate_struct(s).to_string()),
+            ScSpecEntry::UdtUnionV0(u) => Some(rust::r#type::generate_union(u).to_string()),
+            ScSpecEntry::FunctionV0(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// User defined types that more than one contract defines identically, and that only refer to
+/// other shared types. Types with the same name but different definitions stay with each
+/// contract.
+fn shared_types(contracts: &[Contract]) -> Vec<ScSpecEntry> {
+    let mut definitions: BTreeMap<String, Vec<(&str, &ScSpecEntry)>> = BTreeMap::new();
+    for contract in contracts {
+        for entry in &contract.spec {
+            if !matches!(entry, ScSpecEntry::FunctionV0(_)) {
+                definitions
+                    .entry(contractspec::entry_name(entry))
+                    .or_default()
+                    .push((&contract.name, entry));
+            }
+        }
+    }
+
+    let mut shared = vec![];
+    for (name, defs) in definitions {
+        let (first_contract, first) = defs[0];
+        match defs.iter().find(|(_, e)| *e != first) {
+            Some((contract, _)) => eprintln!(
+                "warning: {} is defined differently by {} and {}, so it is not shared",
+                name, first_contract, contract
+            ),
+            None if defs.len() > 1 => shared.push(first.clone()),
+            None => {}
+        }
+    }
+
+    // Drop types that refer to types that aren't shared, until none are left
+    loop {
+        let names = shared.iter().map(udt_name).collect::<Vec<_>>();
+        let before = shared.len();
+        shared.retain(|e| {
+            let mut refs = vec![];
+            match e {
+                ScSpecEntry::UdtStructV0(s) => {
+                    s.fields.iter().for_each(|f| udt_refs(&f.type_, &mut refs));
+                }
+                ScSpecEntry::UdtUnionV0(u) => u
+                    .cases
+                    .iter()
+                    .filter_map(|c| c.type_.as_ref())
+                    .for_each(|t| udt_refs(t, &mut refs)),
+                ScSpecEntry::FunctionV0(_) => {}
+            }
+            refs.iter().all(|r| names.contains(r))
+        });
+        if shared.len() == before {
+            return shared;
+        }
+    }
+}
+
+fn udt_name(entry: &ScSpecEntry) -> String {
+    match entry {
+        ScSpecEntry::FunctionV0(f) => f.name.to_string_lossy(),
+        ScSpecEntry::UdtStructV0(s) => s.name.to_string_lossy(),
+        ScSpecEntry::UdtUnionV0(u) => u.name.to_string_lossy(),
+    }
+}
+
+/// Collects the names of the user defined types a type refers to.
+fn udt_refs(t: &ScSpecTypeDef, refs: &mut Vec<String>) {
+    match t {
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            udt_refs(value_type, refs);
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult {
+                ok_type,
+                error_type,
+            } = &**r;
+            udt_refs(ok_type, refs);
+            udt_refs(error_type, refs);
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            udt_refs(element_type, refs);
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            udt_refs(element_type, refs);
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            udt_refs(key_type, refs);
+            udt_refs(value_type, refs);
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            value_types.iter().for_each(|t| udt_refs(t, refs));
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => refs.push(name.to_string_lossy()),
+        _ => {}
+    }
+}
+
+/// The version of the contracts, from the `version` entry of their contract meta when they all
+/// have the same one. Otherwise, packages are versioned by the hash of the code, as
+/// `0.0.0+<hash>`, which only changes when a contract does.
+fn version(contracts: &[&Contract]) -> String {
+    let versions = contracts
+        .iter()
+        .map(|contract| meta_version(contract.contents.as_deref()?))
+        .collect::<Option<Vec<_>>>();
+    if let Some([first, rest @ ..]) = versions.as_deref() {
+        if rest.iter().all(|v| v == first) {
+            return first.clone();
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    for contract in contracts {
+        match &contract.contents {
+            Some(contents) => hasher.update(contents),
+            None => hasher.update(spec::encode(&contract.spec).unwrap_or_default()),
+        }
+    }
+    format!("0.0.0+{}", &format!("{:x}", hasher.finalize())[..8])
+}
+
+fn meta_version(contents: &[u8]) -> Option<String> {
+    wasm::parse(contents)
+        .ok()?
+        .meta_entries()?
+        .into_iter()
+        .find(|(key, val)| key == "version" && !val.is_empty())
+        .map(|(_, val)| val)
+}
+
+/// Reads a spec given as base64, or as a file containing the spec as xdr or base64. Returns the
+/// spec and a name for the contract.
+fn spec_from_xdr(arg: &str) -> Result<(String, Vec<ScSpecEntry>), Error> {
+    let path = Path::new(arg);
+    let (name, contents) = if path.is_file() {
+        let contents = fs::read(path).map_err(|e| Error::CannotReadSpecFile {
+            filepath: path.to_path_buf(),
+            error: e,
+        })?;
+        // Raw xdr starts with a zero discriminant, so it's never valid base64
+        let decoded = std::str::from_utf8(&contents)
+            .ok()
+            .and_then(|s| base64::decode(s.trim()).ok());
+        (file_stem(path), decoded.unwrap_or(contents))
+    } else {
+        let contents = base64::decode(arg.trim()).map_err(Error::InvalidSpecBase64)?;
+        ("contract".to_string(), contents)
+    };
+    Ok((name, spec::decode(&contents)?))
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Contract names come from file names, which may contain characters that aren't valid in
+/// identifiers.
+fn module_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
+
+/// Package names come from contract and directory names. All of Cargo, npm and Python packaging
+/// accept lowercase letters, digits, `_` and `-`, so anything else is replaced with `_`.
+fn package_name(name: &str) -> Result<String, Error> {
+    let sanitized = name.to_lowercase().replace(
+        |c: char| !matches!(c, 'a'..='z' | '0'..='9' | '_' | '-'),
+        "_",
+    );
+    if sanitized.trim_matches(|c| c == '_' || c == '-').is_empty() {
+        return Err(Error::InvalidPackageName(name.to_string()));
+    }
+    Ok(sanitized)
+}
+
+fn cargo_toml(package: &Package) -> String {
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"{}\"\n",
+        package.name, package.version, SOROBAN_SDK_VERSION
+    )
+}
+
+fn cargo_toml_test(package: &Package) -> String {
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n\n[dev-dependencies]\nsoroban-env-host = {{ version = \"{}\", features = [\"vm\", \"serde\"] }}\nserde_json = \"1\"\n\n{}",
+        package.name, package.version, SOROBAN_ENV_HOST_VERSION, SOROBAN_ENV_HOST_PATCH
+    )
+}
+
+fn package_json(package: &Package) -> String {
+    format!(
+        "{{\n  \"name\": \"{}\",\n  \"version\": \"{}\",\n  \"main\": \"index.ts\",\n  \"types\": \"index.ts\",\n  \"dependencies\": {{\n    \"soroban-client\": \"^{}\"\n  }}\n}}\n",
+        package.name.replace('_', "-"),
+        package.version,
+        SOROBAN_CLIENT_VERSION
+    )
+}
+
+fn pyproject_toml(package: &Package) -> String {
+    format!(
+        "[project]\nname = \"{}\"\nversion = \"{}\"\ndependencies = [\"stellar-sdk>={}\"]\n",
+        package.name, package.version, STELLAR_SDK_VERSION
+    )
+}
+
+/// Writes the file, creating its directory if needed. Files that already have the contents are
+/// left untouched, so regenerating doesn't change anything when the contract hasn't changed.
+fn write_file(filepath: &Path, contents: &[u8]) -> Result<(), Error> {
+    if matches!(fs::read(filepath), Ok(existing) if existing == contents) {
+        return Ok(());
+    }
+    let write = || -> Result<(), io::Error> {
+        if let Some(dir) = filepath.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(filepath, contents)
+    };
+    write().map_err(|e| Error::CannotWriteFile {
+        filepath: filepath.to_path_buf(),
+        error: e,
+    })?;
+    println!("Wrote {}", filepath.to_string_lossy());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cargo_toml_test_uses_the_cli_host() {
+        let package = Package {
+            name: "hello".to_string(),
+            version: "0.0.0".to_string(),
+        };
+        let manifest = cargo_toml_test(&package);
+        assert!(manifest.contains("[patch.crates-io]"));
+        assert!(manifest.contains(
+            "soroban-env-host = { git = \"https://github.com/stellar/rs-soroban-env\", rev = \"0e6cb7aa\" }"
+        ));
+        // The patch mirrors the CLI's own
+        assert!(include_str!("../../Cargo.toml").contains(SOROBAN_ENV_HOST_PATCH));
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("Hello World").unwrap(), "hello_world");
+        assert_eq!(package_name("token-v2.1").unwrap(), "token-v2_1");
+        assert_eq!(package_name("héllo").unwrap(), "h_llo");
+        assert!(matches!(
+            package_name(""),
+            Err(Error::InvalidPackageName(_))
+        ));
+        assert!(matches!(
+            package_name("..."),
+            Err(Error::InvalidPackageName(_))
+        ));
+    }
+
+    #[test]
+    fn test_version() {
+        let contract = |meta: Option<&str>| {
+            let mut contents = crate::testutils::contract();
+            if let Some(version) = meta {
+                // An SC_META_V0 entry, with the key and value padded to 4 bytes
+                let mut entry = vec![0, 0, 0, 0, 0, 0, 0, 7];
+                entry.extend_from_slice(b"version\0");
+                entry.extend_from_slice(&u32::try_from(version.len()).unwrap().to_be_bytes());
+                entry.extend_from_slice(version.as_bytes());
+                entry.resize((entry.len() + 3) / 4 * 4, 0);
+                let section = wasm::RawSection::custom("contractmetav0", &entry);
+                contents = wasm::encode_sections(
+                    &contents,
+                    wasm::raw_sections(&contents)
+                        .unwrap()
+                        .iter()
+                        .map(|s| (s.id, s.contents))
+                        .chain([(0, section.as_slice())]),
+                );
+            }
+            let mut contract = Contract::from_spec("hello".to_string(), vec![]);
+            contract.contents = Some(contents);
+            contract
+        };
+
+        let versioned = contract(Some("1.2.0"));
+        assert_eq!(version(&[&versioned]), "1.2.0");
+        assert_eq!(version(&[&versioned, &contract(Some("1.2.0"))]), "1.2.0");
+        // Without a version, or with different ones, the hash of the code is used
+        let unversioned = contract(None);
+        let hashed = version(&[&unversioned]);
+        assert!(hashed.starts_with("0.0.0+"));
+        assert_eq!(hashed.len(), "0.0.0+".len() + 8);
+        assert!(version(&[&versioned, &contract(Some("2.0.0"))]).starts_with("0.0.0+"));
+        assert!(version(&[&versioned, &unversioned]).starts_with("0.0.0+"));
+    }
+}
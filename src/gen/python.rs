@@ -0,0 +1,871 @@
+use std::fmt::Write;
+
+use soroban_env_host::xdr::{
+    ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption,
+    ScSpecTypeResult, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+    ScSpecUdtStructV0, ScSpecUdtUnionV0,
+};
+
+/// Python keywords that are valid Rust identifiers, and so may appear as names in a spec.
+const KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "class", "def", "del", "elif", "except", "finally",
+    "from", "global", "import", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "try",
+    "with", "yield",
+];
+
+const IMPORTS: &str = r#"import base64
+import binascii
+import dataclasses
+import json
+import time
+import typing
+import urllib.request
+
+from stellar_sdk import Account, Keypair, TransactionBuilder, xdr
+from stellar_sdk.operation import Operation
+"#;
+
+/// Names defined by `RUNTIME` that the other generated modules use. They are private to the
+/// package, so they have to be imported by name.
+const RUNTIME_EXPORTS: &[&str] = &[
+    "_obj",
+    "_scobject",
+    "_none",
+    "_is_none",
+    "_u32",
+    "_i32",
+    "_u64",
+    "_i64",
+    "_bigint",
+    "_from_bigint",
+    "_bitset",
+    "_status",
+    "_bool",
+    "_symbol",
+    "_bytes",
+    "_vec",
+    "_map",
+    "_unsupported",
+    "_field",
+    "_contract_id_bytes",
+    "_rpc",
+    "_InvokeHostFunction",
+];
+
+/// Helpers shared by the generated encoders, decoders and client.
+const RUNTIME: &str = r#"
+
+def _obj(o: xdr.SCObject) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_OBJECT, obj=o)
+
+
+def _scobject(v: xdr.SCVal) -> xdr.SCObject:
+    if v.type != xdr.SCValType.SCV_OBJECT or v.obj is None:
+        raise ValueError("expected an object value")
+    return v.obj
+
+
+def _none() -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_OBJECT, obj=None)
+
+
+def _is_none(v: xdr.SCVal) -> bool:
+    return v.type == xdr.SCValType.SCV_OBJECT and v.obj is None
+
+
+def _u32(v: int) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_U32, u32=xdr.Uint32(v))
+
+
+def _i32(v: int) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_I32, i32=xdr.Int32(v))
+
+
+def _u64(v: int) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_U63, u63=xdr.Int64(v))
+
+
+def _i64(v: int) -> xdr.SCVal:
+    return _obj(xdr.SCObject(type=xdr.SCObjectType.SCO_I64, i64=xdr.Int64(v)))
+
+
+def _bigint(v: int) -> xdr.SCVal:
+    if v == 0:
+        big = xdr.SCBigInt(sign=xdr.SCNumSign.ZERO)
+    else:
+        magnitude = abs(v).to_bytes((abs(v).bit_length() + 7) // 8, "big")
+        sign = xdr.SCNumSign.NEGATIVE if v < 0 else xdr.SCNumSign.POSITIVE
+        big = xdr.SCBigInt(sign=sign, magnitude=magnitude)
+    return _obj(xdr.SCObject(type=xdr.SCObjectType.SCO_BIG_INT, big_int=big))
+
+
+def _from_bigint(v: xdr.SCVal) -> int:
+    big = _scobject(v).big_int
+    if big.sign == xdr.SCNumSign.ZERO:
+        return 0
+    magnitude = int.from_bytes(big.magnitude, "big")
+    return -magnitude if big.sign == xdr.SCNumSign.NEGATIVE else magnitude
+
+
+def _bitset(v: int) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_BITSET, bits=xdr.Uint64(v))
+
+
+def _status(v: xdr.SCStatus) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_STATUS, status=v)
+
+
+def _bool(v: bool) -> xdr.SCVal:
+    return xdr.SCVal(
+        type=xdr.SCValType.SCV_STATIC,
+        ic=xdr.SCStatic.SCS_TRUE if v else xdr.SCStatic.SCS_FALSE,
+    )
+
+
+def _symbol(v: str) -> xdr.SCVal:
+    return xdr.SCVal(type=xdr.SCValType.SCV_SYMBOL, sym=xdr.SCSymbol(v.encode()))
+
+
+def _bytes(v: bytes) -> xdr.SCVal:
+    return _obj(xdr.SCObject(type=xdr.SCObjectType.SCO_BYTES, bin=bytes(v)))
+
+
+def _vec(v: typing.List[xdr.SCVal]) -> xdr.SCVal:
+    return _obj(xdr.SCObject(type=xdr.SCObjectType.SCO_VEC, vec=xdr.SCVec(v)))
+
+
+def _map_key(v: xdr.SCVal) -> typing.Tuple[typing.Any, ...]:
+    """Orders map keys the way the host does, by type and then by value."""
+    if v.type == xdr.SCValType.SCV_U63:
+        return (v.type.value, v.u63.int64)
+    if v.type == xdr.SCValType.SCV_U32:
+        return (v.type.value, v.u32.uint32)
+    if v.type == xdr.SCValType.SCV_I32:
+        return (v.type.value, v.i32.int32)
+    if v.type == xdr.SCValType.SCV_STATIC:
+        return (v.type.value, v.ic.value)
+    if v.type == xdr.SCValType.SCV_SYMBOL:
+        return (v.type.value, v.sym.sc_symbol)
+    if v.type == xdr.SCValType.SCV_BITSET:
+        return (v.type.value, v.bits.uint64)
+    if v.type == xdr.SCValType.SCV_OBJECT and v.obj is not None:
+        if v.obj.type == xdr.SCObjectType.SCO_BYTES:
+            return (v.type.value, v.obj.type.value, bytes(v.obj.bin))
+        if v.obj.type == xdr.SCObjectType.SCO_I64:
+            return (v.type.value, v.obj.type.value, v.obj.i64.int64)
+        return (v.type.value, v.obj.type.value, v.to_xdr_bytes())
+    # Keys without a simpler order are compared by their XDR
+    return (v.type.value, v.to_xdr_bytes())
+
+
+def _map(v: typing.List[typing.Tuple[xdr.SCVal, xdr.SCVal]]) -> xdr.SCVal:
+    # Maps must be sorted by key
+    entries = [
+        xdr.SCMapEntry(key=k, val=val) for k, val in sorted(v, key=lambda e: _map_key(e[0]))
+    ]
+    return _obj(xdr.SCObject(type=xdr.SCObjectType.SCO_MAP, map=xdr.SCMap(entries)))
+
+
+def _unsupported(message: str) -> typing.NoReturn:
+    raise TypeError(message)
+
+
+def _field(v: xdr.SCVal, name: str) -> xdr.SCVal:
+    for entry in _scobject(v).map.sc_map:
+        if entry.key.sym.sc_symbol.decode() == name:
+            return entry.val
+    raise ValueError(f"missing field {name}")
+
+
+def _contract_id_bytes(contract_id: str) -> bytes:
+    if len(contract_id) == 64:
+        try:
+            return bytes.fromhex(contract_id)
+        except ValueError:
+            pass
+    # A strkey is the version byte, the ID and a CRC16-XMODEM checksum, base32 encoded
+    try:
+        decoded = base64.b32decode(contract_id) if len(contract_id) == 56 else b""
+    except binascii.Error:
+        decoded = b""
+    if (
+        len(decoded) != 35
+        or decoded[0] != 2 << 3
+        or binascii.crc_hqx(decoded[:33], 0).to_bytes(2, "little") != decoded[33:]
+    ):
+        raise ValueError(f"contract ID {contract_id} must be 64 hex characters or a C... strkey")
+    return decoded[1:33]
+
+def _rpc(url: str, method: str, params: typing.List[typing.Any]) -> typing.Any:
+    body = json.dumps({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+    request = urllib.request.Request(
+        url, data=body.encode(), headers={"Content-Type": "application/json"}
+    )
+    with urllib.request.urlopen(request) as response:
+        result = json.load(response)
+    if "error" in result:
+        raise RuntimeError(f"{method}: {result['error']['message']}")
+    return result["result"]
+
+
+class _InvokeHostFunction(Operation):
+    def __init__(self, parameters: typing.List[xdr.SCVal], footprint: xdr.LedgerFootprint) -> None:
+        super().__init__()
+        self.parameters = parameters
+        self.footprint = footprint
+
+    @classmethod
+    def type_code(cls) -> xdr.OperationType:
+        return xdr.OperationType.INVOKE_HOST_FUNCTION
+
+    def _to_operation_body(self) -> xdr.OperationBody:
+        op = xdr.InvokeHostFunctionOp(
+            function=xdr.HostFunction.HOST_FN_CALL,
+            parameters=xdr.SCVec(self.parameters),
+            footprint=self.footprint,
+        )
+        return xdr.OperationBody(type=self.type_code(), invoke_host_function_op=op)
+"#;
+
+const CLIENT: &str = r#"
+
+class Contract:
+    """Client for the contract with the ID, given as a C... strkey or 64 hex characters."""
+
+    def __init__(
+        self,
+        contract_id: str,
+        rpc_url: str = "http://localhost:8080/api/v1/jsonrpc",
+        network_passphrase: str = "Local Sandbox Stellar Network ; September 2022",
+        keypair: typing.Optional[Keypair] = None,
+        timeout: float = 10.0,
+    ) -> None:
+        self.contract_id = contract_id
+        self._contract_id = _contract_id_bytes(contract_id)
+        self.rpc_url = rpc_url
+        self.network_passphrase = network_passphrase
+        self.keypair = keypair or Keypair.random()
+        # Seconds to wait for a sent transaction to leave the pending status
+        self.timeout = timeout
+
+    def _transaction(
+        self, params: typing.List[xdr.SCVal], footprint: xdr.LedgerFootprint
+    ) -> str:
+        # The sandbox doesn't check sequence numbers
+        tx = (
+            TransactionBuilder(
+                Account(self.keypair.public_key, 0),
+                network_passphrase=self.network_passphrase,
+                base_fee=100,
+            )
+            .append_operation(_InvokeHostFunction(params, footprint))
+            .set_timeout(0)
+            .build()
+        )
+        tx.sign(self.keypair)
+        return tx.to_xdr()
+
+    def _invoke(self, method: str, args: typing.List[xdr.SCVal], send: bool) -> xdr.SCVal:
+        params = [_bytes(self._contract_id), _symbol(method), *args]
+        empty = xdr.LedgerFootprint(read_only=[], read_write=[])
+        simulated = _rpc(self.rpc_url, "simulateTransaction", [self._transaction(params, empty)])
+        if not send:
+            return xdr.SCVal.from_xdr(simulated["results"][0]["xdr"])
+
+        footprint = xdr.LedgerFootprint(
+            read_only=[xdr.LedgerKey.from_xdr(k) for k in simulated["footprint"]["readOnly"]],
+            read_write=[xdr.LedgerKey.from_xdr(k) for k in simulated["footprint"]["readWrite"]],
+        )
+        sent = _rpc(self.rpc_url, "sendTransaction", [self._transaction(params, footprint)])
+        status = sent
+        deadline = time.monotonic() + self.timeout
+        while status["status"] == "pending":
+            if time.monotonic() >= deadline:
+                raise TimeoutError(f"{method}: transaction {sent['id']} is still pending")
+            time.sleep(0.1)
+            status = _rpc(self.rpc_url, "getTransactionStatus", [sent["id"]])
+        if status["status"] != "success":
+            raise RuntimeError(f"{method}: {status.get('error')}")
+        return xdr.SCVal.from_xdr(status["results"][0]["results"][0]["xdr"])
+"#;
+
+/// Generates a Python module with a dataclass, encoder and decoder for each user defined type in
+/// the spec, and a `Contract` client with one method per function.
+pub fn generate(spec: &[ScSpecEntry]) -> String {
+    let mut out = String::new();
+    out.push_str("# This file was generated by soroban-cli. Do not edit.\n");
+    out.push_str(IMPORTS);
+    out.push_str(RUNTIME);
+    generate_types(&mut out, spec);
+    generate_client(&mut out, spec);
+    out
+}
+
+/// Generates a Python module with the helpers and the types shared by several contracts, for
+/// use by the modules generated by `generate_contract`.
+pub fn generate_shared(shared: &[ScSpecEntry]) -> String {
+    let mut out = String::new();
+    out.push_str("# This file was generated by soroban-cli. Do not edit.\n");
+    out.push_str(IMPORTS);
+    out.push_str(RUNTIME);
+    generate_types(&mut out, shared);
+    out
+}
+
+/// Generates a Python module for one contract, importing the helpers and shared types from the
+/// `types` module generated by `generate_shared`.
+pub fn generate_contract(spec: &[ScSpecEntry], shared: &[ScSpecEntry]) -> String {
+    let mut imports = RUNTIME_EXPORTS
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    for entry in shared {
+        match entry {
+            ScSpecEntry::UdtStructV0(s) => {
+                let name = s.name.to_string_lossy();
+                imports.push(format!("_encode_{}", name));
+                imports.push(format!("_decode_{}", name));
+                imports.push(name);
+            }
+            ScSpecEntry::UdtUnionV0(u) => {
+                let name = u.name.to_string_lossy();
+                imports.push(format!("_encode_{}", name));
+                imports.push(format!("_decode_{}", name));
+                imports.extend(
+                    u.cases
+                        .iter()
+                        .map(|c| format!("{}{}", name, c.name.to_string_lossy())),
+                );
+                imports.push(name);
+            }
+            ScSpecEntry::FunctionV0(_) => {}
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("# This file was generated by soroban-cli. Do not edit.\n");
+    out.push_str(IMPORTS);
+    out.push_str("\nfrom .types import (\n");
+    for name in imports {
+        writeln!(out, "    {},", name).unwrap();
+    }
+    out.push_str(")\n");
+    generate_types(&mut out, spec);
+    generate_client(&mut out, spec);
+    out
+}
+
+fn generate_types(out: &mut String, spec: &[ScSpecEntry]) {
+    for entry in spec {
+        match entry {
+            ScSpecEntry::UdtStructV0(s) => generate_struct(out, s),
+            ScSpecEntry::UdtUnionV0(u) => generate_union(out, u),
+            ScSpecEntry::FunctionV0(_) => {}
+        }
+    }
+}
+
+fn generate_client(out: &mut String, spec: &[ScSpecEntry]) {
+    out.push_str(CLIENT);
+    for entry in spec {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            generate_function(out, f);
+        }
+    }
+}
+
+/// Appends an underscore to names that are Python keywords, as PEP 8 suggests.
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn generate_struct(out: &mut String, s: &ScSpecUdtStructV0) {
+    let name = s.name.to_string_lossy();
+    writeln!(out, "\n\n@dataclasses.dataclass\nclass {}:", name).unwrap();
+    if s.fields.is_empty() {
+        out.push_str("    pass\n");
+    }
+    for f in s.fields.iter() {
+        writeln!(
+            out,
+            "    {}: {}",
+            ident(&f.name.to_string_lossy()),
+            type_hint(&f.type_)
+        )
+        .unwrap();
+    }
+
+    // Structs are encoded as a map keyed by field name, and maps must be sorted by key
+    let mut fields = s.fields.iter().collect::<Vec<_>>();
+    fields.sort_by_key(|f| f.name.to_string_lossy());
+    writeln!(
+        out,
+        "\n\ndef _encode_{}(v: \"{}\") -> xdr.SCVal:\n    return _map([",
+        name, name
+    )
+    .unwrap();
+    for f in &fields {
+        let field = f.name.to_string_lossy();
+        writeln!(
+            out,
+            "        (_symbol(\"{}\"), {}),",
+            field,
+            encode(&f.type_, &format!("v.{}", ident(&field)))
+        )
+        .unwrap();
+    }
+    out.push_str("    ])\n");
+
+    writeln!(
+        out,
+        "\n\ndef _decode_{}(v: xdr.SCVal) -> \"{}\":\n    return {}(",
+        name, name, name
+    )
+    .unwrap();
+    for f in s.fields.iter() {
+        let field = f.name.to_string_lossy();
+        writeln!(
+            out,
+            "        {}={},",
+            ident(&field),
+            decode(&f.type_, &format!("_field(v, \"{}\")", field))
+        )
+        .unwrap();
+    }
+    out.push_str("    )\n");
+}
+
+fn generate_union(out: &mut String, u: &ScSpecUdtUnionV0) {
+    let name = u.name.to_string_lossy();
+    // Each case is a dataclass of its own, and the union is a type alias over them
+    let mut cases = vec![];
+    for c in u.cases.iter() {
+        let case = format!("{}{}", name, c.name.to_string_lossy());
+        writeln!(out, "\n\n@dataclasses.dataclass\nclass {}:", case).unwrap();
+        match &c.type_ {
+            Some(t) => writeln!(out, "    value: {}", type_hint(t)).unwrap(),
+            None => out.push_str("    pass\n"),
+        }
+        cases.push(case);
+    }
+    writeln!(
+        out,
+        "\n\n{} = typing.Union[{}]",
+        name,
+        cases
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+
+    // Unions are encoded as a vec of the case name followed by the value, if any
+    writeln!(
+        out,
+        "\n\ndef _encode_{}(v: \"{}\") -> xdr.SCVal:",
+        name, name
+    )
+    .unwrap();
+    for c in u.cases.iter() {
+        let case = c.name.to_string_lossy();
+        let value = c
+            .type_
+            .as_ref()
+            .map(|t| format!(", {}", encode(t, "v.value")))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "    if isinstance(v, {}{}):\n        return _vec([_symbol(\"{}\"){}])",
+            name, case, case, value
+        )
+        .unwrap();
+    }
+    writeln!(out, "    raise TypeError(f\"not a {}: {{v!r}}\")", name).unwrap();
+
+    writeln!(
+        out,
+        "\n\ndef _decode_{}(v: xdr.SCVal) -> \"{}\":\n    vec = _scobject(v).vec.sc_vec\n    tag = vec[0].sym.sc_symbol.decode()",
+        name, name
+    )
+    .unwrap();
+    for c in u.cases.iter() {
+        let case = c.name.to_string_lossy();
+        let value = c
+            .type_
+            .as_ref()
+            .map(|t| decode(t, "vec[1]"))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "    if tag == \"{}\":\n        return {}{}({})",
+            case, name, case, value
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "    raise ValueError(f\"unknown {} case {{tag}}\")",
+        name
+    )
+    .unwrap();
+}
+
+fn generate_function(out: &mut String, f: &ScSpecFunctionV0) {
+    let name = f.name.to_string_lossy();
+    let mut params = vec!["self".to_string()];
+    params.extend(f.inputs.iter().map(|i| {
+        format!(
+            "{}: {}",
+            ident(&i.name.to_string_lossy()),
+            type_hint(&i.type_)
+        )
+    }));
+    params.push("send: bool = False".to_string());
+    let args = f
+        .inputs
+        .iter()
+        .map(|i| encode(&i.type_, &ident(&i.name.to_string_lossy())))
+        .collect::<Vec<_>>();
+    let (ret, result) = match f.outputs.as_slice() {
+        [] => ("None".to_string(), None),
+        [t] => (type_hint(t), Some(decode(t, "result"))),
+        outputs => {
+            let tuple = ScSpecTypeDef::Tuple(Box::new(ScSpecTypeTuple {
+                value_types: outputs.to_vec().try_into().unwrap_or_default(),
+            }));
+            (type_hint(&tuple), Some(decode(&tuple, "result")))
+        }
+    };
+
+    writeln!(
+        out,
+        "\n    def {}({}) -> {}:",
+        ident(&name),
+        params.join(", "),
+        ret
+    )
+    .unwrap();
+    let call = format!("self._invoke(\"{}\", [{}], send)", name, args.join(", "));
+    match result {
+        Some(result) => {
+            writeln!(out, "        result = {}", call).unwrap();
+            writeln!(out, "        return {}", result).unwrap();
+        }
+        None => writeln!(out, "        {}", call).unwrap(),
+    }
+}
+
+/// The Python type hint for a spec type. User defined types are quoted so they can be referenced
+/// before they are defined.
+pub fn type_hint(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::U32
+        | ScSpecTypeDef::I32
+        | ScSpecTypeDef::U64
+        | ScSpecTypeDef::I64
+        | ScSpecTypeDef::BigInt
+        | ScSpecTypeDef::Bitset => "int".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Symbol => "str".to_string(),
+        ScSpecTypeDef::Bytes => "bytes".to_string(),
+        ScSpecTypeDef::Status => "xdr.SCStatus".to_string(),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!("typing.Optional[{}]", type_hint(value_type))
+        }
+        // Errors are returned by the host as a failed invocation, so only the ok type is ever
+        // returned
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult { ok_type, .. } = &**r;
+            type_hint(ok_type)
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!("typing.List[{}]", type_hint(element_type))
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!("typing.List[{}]", type_hint(element_type))
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            format!(
+                "typing.Dict[{}, {}]",
+                type_hint(key_type),
+                type_hint(value_type)
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let types = value_types.iter().map(type_hint).collect::<Vec<_>>();
+            format!("typing.Tuple[{}]", types.join(", "))
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => format!("\"{}\"", name.to_string_lossy()),
+    }
+}
+
+/// A Python expression that encodes the value of `expr` to an `xdr.SCVal`, following the same
+/// conventions as `strval`.
+fn encode(t: &ScSpecTypeDef, expr: &str) -> String {
+    match t {
+        ScSpecTypeDef::U32 => format!("_u32({})", expr),
+        ScSpecTypeDef::I32 => format!("_i32({})", expr),
+        ScSpecTypeDef::U64 => format!("_u64({})", expr),
+        ScSpecTypeDef::I64 => format!("_i64({})", expr),
+        ScSpecTypeDef::BigInt => format!("_bigint({})", expr),
+        ScSpecTypeDef::Bitset => format!("_bitset({})", expr),
+        ScSpecTypeDef::Status => format!("_status({})", expr),
+        ScSpecTypeDef::Bool => format!("_bool({})", expr),
+        ScSpecTypeDef::Symbol => format!("_symbol({})", expr),
+        ScSpecTypeDef::Bytes => format!("_bytes({})", expr),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!(
+                "(lambda v: _none() if v is None else {})({})",
+                encode(value_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Result(_) => {
+            "_unsupported(\"a Result cannot be passed as an argument\")".to_string()
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!("_vec([{} for v in {}])", encode(element_type, "v"), expr)
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!("_vec([{} for v in {}])", encode(element_type, "v"), expr)
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            // _map sorts the entries by key
+            format!(
+                "_map([({}, {}) for k, v in {}.items()])",
+                encode(key_type, "k"),
+                encode(value_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let values = value_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| encode(t, &format!("v[{}]", i)))
+                .collect::<Vec<_>>();
+            format!("(lambda v: _vec([{}]))({})", values.join(", "), expr)
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            format!("_encode_{}({})", name.to_string_lossy(), expr)
+        }
+    }
+}
+
+/// A Python expression that decodes the `xdr.SCVal` in `expr` to the type's Python
+/// representation.
+fn decode(t: &ScSpecTypeDef, expr: &str) -> String {
+    match t {
+        ScSpecTypeDef::U32 => format!("{}.u32.uint32", expr),
+        ScSpecTypeDef::I32 => format!("{}.i32.int32", expr),
+        ScSpecTypeDef::U64 => format!("{}.u63.int64", expr),
+        ScSpecTypeDef::I64 => format!("_scobject({}).i64.int64", expr),
+        ScSpecTypeDef::BigInt => format!("_from_bigint({})", expr),
+        ScSpecTypeDef::Bitset => format!("{}.bits.uint64", expr),
+        ScSpecTypeDef::Status => format!("{}.status", expr),
+        ScSpecTypeDef::Bool => format!("({}.ic == xdr.SCStatic.SCS_TRUE)", expr),
+        ScSpecTypeDef::Symbol => format!("{}.sym.sc_symbol.decode()", expr),
+        ScSpecTypeDef::Bytes => format!("bytes(_scobject({}).bin)", expr),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!(
+                "(lambda v: None if _is_none(v) else {})({})",
+                decode(value_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult { ok_type, .. } = &**r;
+            decode(ok_type, expr)
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!(
+                "[{} for v in _scobject({}).vec.sc_vec]",
+                decode(element_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!(
+                "[{} for v in _scobject({}).vec.sc_vec]",
+                decode(element_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            format!(
+                "{{{}: {} for e in _scobject({}).map.sc_map}}",
+                decode(key_type, "e.key"),
+                decode(value_type, "e.val"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let values = value_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| decode(t, &format!("v[{}]", i)))
+                .collect::<Vec<_>>();
+            if values.is_empty() {
+                return "()".to_string();
+            }
+            // A trailing comma keeps a single element tuple a tuple
+            format!(
+                "(lambda v: ({},))(_scobject({}).vec.sc_vec)",
+                values.join(", "),
+                expr
+            )
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            format!("_decode_{}({})", name.to_string_lossy(), expr)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_env_host::xdr::{
+        ScSpecFunctionInputV0, ScSpecUdtStructFieldV0, ScSpecUdtUnionCaseV0,
+    };
+
+    fn spec() -> Vec<ScSpecEntry> {
+        let udt = |name: &str| {
+            ScSpecTypeDef::Udt(ScSpecTypeUdt {
+                name: name.as_bytes().try_into().unwrap(),
+            })
+        };
+        vec![
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                name: "Config".as_bytes().try_into().unwrap(),
+                fields: vec![
+                    ScSpecUdtStructFieldV0 {
+                        name: "limit".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::U32,
+                    },
+                    ScSpecUdtStructFieldV0 {
+                        name: "from".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::Symbol,
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+                name: "Key".as_bytes().try_into().unwrap(),
+                cases: vec![
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Admin".as_bytes().try_into().unwrap(),
+                        type_: None,
+                    },
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Balance".as_bytes().try_into().unwrap(),
+                        type_: Some(ScSpecTypeDef::U32),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+                name: "set".as_bytes().try_into().unwrap(),
+                inputs: [
+                    ("config", udt("Config")),
+                    ("key", udt("Key")),
+                    (
+                        "weights",
+                        ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+                            key_type: ScSpecTypeDef::Symbol,
+                            value_type: ScSpecTypeDef::U32,
+                        })),
+                    ),
+                    (
+                        "status",
+                        ScSpecTypeDef::Result(Box::new(ScSpecTypeResult {
+                            ok_type: ScSpecTypeDef::U32,
+                            error_type: ScSpecTypeDef::U32,
+                        })),
+                    ),
+                ]
+                .into_iter()
+                .map(|(name, type_)| ScSpecFunctionInputV0 {
+                    name: name.as_bytes().try_into().unwrap(),
+                    type_,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+                outputs: vec![ScSpecTypeDef::U32].try_into().unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_struct() {
+        let out = generate(&spec());
+        assert!(
+            out.contains("@dataclasses.dataclass\nclass Config:\n    limit: int\n    from_: str\n")
+        );
+        // Fields are encoded sorted by name, and keywords get a trailing underscore
+        assert!(out.contains(
+            "def _encode_Config(v: \"Config\") -> xdr.SCVal:\n    return _map([\n        (_symbol(\"from\"), _symbol(v.from_)),\n        (_symbol(\"limit\"), _u32(v.limit)),\n    ])\n"
+        ));
+        assert!(out.contains("        from_=_field(v, \"from\").sym.sc_symbol.decode(),\n"));
+    }
+
+    #[test]
+    fn test_union() {
+        let out = generate(&spec());
+        assert!(out.contains("@dataclasses.dataclass\nclass KeyAdmin:\n    pass\n"));
+        assert!(out.contains("@dataclasses.dataclass\nclass KeyBalance:\n    value: int\n"));
+        assert!(out.contains("Key = typing.Union[\"KeyAdmin\", \"KeyBalance\"]\n"));
+        assert!(out.contains(
+            "    if isinstance(v, KeyBalance):\n        return _vec([_symbol(\"Balance\"), _u32(v.value)])\n"
+        ));
+        assert!(out.contains(
+            "    if tag == \"Balance\":\n        return KeyBalance(vec[1].u32.uint32)\n"
+        ));
+    }
+
+    #[test]
+    fn test_function() {
+        let out = generate(&spec());
+        assert!(out.contains(
+            "    def set(self, config: \"Config\", key: \"Key\", weights: typing.Dict[str, int], status: int, send: bool = False) -> int:\n"
+        ));
+        assert!(out.contains(
+            "        result = self._invoke(\"set\", [_encode_Config(config), _encode_Key(key), _map([(_symbol(k), _u32(v)) for k, v in weights.items()]), _unsupported(\"a Result cannot be passed as an argument\")], send)\n"
+        ));
+        assert!(out.contains("        return result.u32.uint32\n"));
+        // The helpers are emitted with the runtime
+        assert!(out.contains("\ndef _unsupported(message: str) -> typing.NoReturn:\n"));
+        assert!(out.contains("sorted(v, key=lambda e: _map_key(e[0]))"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+
+use soroban_env_host::xdr::{self, ScSpecEntry, ScSpecFunctionV0, ScStatic, ScVal, WriteXdr};
+
+use crate::contractspec;
+
+/// Setup shared by the generated tests. The snapshot source is the same as `snapshot::Snap`.
+const SETUP: &str = r#"
+use std::rc::Rc;
+
+use soroban_env_host::{
+    budget::Budget,
+    im_rc::OrdMap,
+    storage::{SnapshotSource, Storage},
+    xdr::{
+        ContractDataEntry, HostFunction, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey,
+        LedgerKeyContractData, ReadXdr, ScContractCode, ScHostStorageErrorCode, ScObject,
+        ScStatic, ScStatus, ScVal,
+    },
+    Host, HostError,
+};
+
+struct Snap {
+    ledger_entries: OrdMap<LedgerKey, LedgerEntry>,
+}
+
+impl SnapshotSource for Snap {
+    fn get(&self, key: &LedgerKey) -> Result<LedgerEntry, HostError> {
+        match self.ledger_entries.get(key) {
+            Some(v) => Ok(v.clone()),
+            None => Err(ScStatus::HostStorageError(ScHostStorageErrorCode::UnknownError).into()),
+        }
+    }
+    fn has(&self, key: &LedgerKey) -> Result<bool, HostError> {
+        Ok(self.ledger_entries.contains_key(key))
+    }
+}
+
+/// A host with the ledger snapshot loaded, and the contract registered at its ID.
+fn host() -> Host {
+    let mut ledger_entries = ledger_entries();
+    if let Some(wasm) = WASM {
+        let key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract_id: CONTRACT_ID.into(),
+            key: ScVal::Static(ScStatic::LedgerKeyContractCode),
+        });
+        let data = LedgerEntryData::ContractData(ContractDataEntry {
+            contract_id: CONTRACT_ID.into(),
+            key: ScVal::Static(ScStatic::LedgerKeyContractCode),
+            val: ScVal::Object(Some(ScObject::ContractCode(ScContractCode::Wasm(
+                wasm.to_vec().try_into().unwrap(),
+            )))),
+        });
+        let entry = LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data,
+            ext: LedgerEntryExt::V0,
+        };
+        ledger_entries.insert(key, entry);
+    }
+    let snap = Rc::new(Snap { ledger_entries });
+    Host::with_storage_and_budget(Storage::with_recording_footprint(snap), Budget::default())
+}
+
+fn invoke(host: &Host, function: &str, args: &[&str]) -> Result<ScVal, HostError> {
+    let mut complete_args = vec![
+        ScVal::Object(Some(ScObject::Bytes(CONTRACT_ID.try_into().unwrap()))),
+        ScVal::Symbol(function.as_bytes().try_into().unwrap()),
+    ];
+    for arg in args {
+        complete_args.push(ScVal::from_xdr_base64(arg.to_string()).unwrap());
+    }
+    host.invoke_function(HostFunction::Call, complete_args.try_into().unwrap())
+}
+"#;
+
+/// The ledger snapshot the tests start from, read from `LEDGER_FILE`.
+const LEDGER_FROM_FILE: &str = r#"
+fn ledger_entries() -> OrdMap<LedgerKey, LedgerEntry> {
+    let file = std::fs::File::open(LEDGER_FILE)
+        .unwrap_or_else(|e| panic!("cannot open ledger file {}: {}", LEDGER_FILE, e));
+    let state: soroban_env_host::xdr::VecM<(LedgerKey, LedgerEntry)> =
+        serde_json::from_reader(file).unwrap();
+    state.iter().cloned().collect()
+}
+"#;
+
+/// The tests start from an empty ledger when there is no snapshot.
+const LEDGER_EMPTY: &str = r#"
+fn ledger_entries() -> OrdMap<LedgerKey, LedgerEntry> {
+    OrdMap::new()
+}
+"#;
+
+/// Generates a test module that invokes each function of the contract, with placeholder
+/// arguments, on a host loaded with the ledger snapshot from `ledger_file` if given. The contract
+/// is registered at its ID from `wasm_file` if given, otherwise it must already be in the
+/// snapshot. Both files are relative to the root of the crate the tests are in. The tests are
+/// ignored until the placeholder arguments are replaced.
+pub fn generate(
+    spec: &[ScSpecEntry],
+    contract_id: [u8; 32],
+    wasm_file: Option<&str>,
+    ledger_file: Option<&str>,
+) -> Result<String, xdr::Error> {
+    let mut out = String::new();
+    out.push_str("#![cfg(test)]\n// This file was generated by soroban-cli.\n");
+    if let Some(file) = ledger_file {
+        writeln!(
+            out,
+            "const LEDGER_FILE: &str = concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/\", {:?});",
+            file
+        )
+        .unwrap();
+    }
+    writeln!(out, "const CONTRACT_ID: [u8; 32] = {:?};", contract_id).unwrap();
+    match wasm_file {
+        Some(file) => writeln!(
+            out,
+            "const WASM: Option<&[u8]> = Some(include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/\", {:?})));",
+            file
+        ),
+        None => writeln!(out, "const WASM: Option<&[u8]> = None;"),
+    }
+    .unwrap();
+    out.push_str(SETUP);
+    out.push_str(if ledger_file.is_some() {
+        LEDGER_FROM_FILE
+    } else {
+        LEDGER_EMPTY
+    });
+
+    for entry in spec {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            generate_test(&mut out, f, spec)?;
+        }
+    }
+    Ok(out)
+}
+
+fn generate_test(
+    out: &mut String,
+    f: &ScSpecFunctionV0,
+    spec: &[ScSpecEntry],
+) -> Result<(), xdr::Error> {
+    let name = f.name.to_string_lossy();
+    let mut args = vec![];
+    for i in f.inputs.iter() {
+        let arg = contractspec::example_value(&i.type_, spec)
+            .unwrap_or(ScVal::Static(ScStatic::Void))
+            .to_xdr_base64()?;
+        args.push(format!(
+            "// {}: {}\n{:?},",
+            i.name.to_string_lossy(),
+            contractspec::type_to_string(&i.type_),
+            arg
+        ));
+    }
+    writeln!(
+        out,
+        "\n/// {}\n#[test]\n#[ignore = \"replace the placeholder arguments with ones that are valid for the contract\"]\nfn test_{}() {{\n    let host = host();\n    let res = invoke(&host, {:?}, &[\n{}\n    ]);\n    assert!(res.is_ok(), \"{{:?}}\", res);\n}}",
+        contractspec::function_signature(f),
+        name,
+        name,
+        args.join("\n")
+    )
+    .unwrap();
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_env_host::xdr::ScSpecFunctionInputV0;
+
+    fn spec() -> Vec<ScSpecEntry> {
+        vec![ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+            name: "hello".as_bytes().try_into().unwrap(),
+            inputs: vec![ScSpecFunctionInputV0 {
+                name: "to".as_bytes().try_into().unwrap(),
+                type_: xdr::ScSpecTypeDef::Symbol,
+            }]
+            .try_into()
+            .unwrap(),
+            outputs: vec![].try_into().unwrap(),
+        })]
+    }
+
+    #[test]
+    fn test_ledger_file() {
+        let code = generate(&spec(), [0; 32], None, Some("tests/fixtures/ledger.json")).unwrap();
+        assert!(code.contains(
+            "const LEDGER_FILE: &str = concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/\", \"tests/fixtures/ledger.json\");"
+        ));
+        assert!(code.contains("cannot open ledger file"));
+
+        let code = generate(&spec(), [0; 32], None, None).unwrap();
+        assert!(!code.contains("LEDGER_FILE"));
+        assert!(code.contains("OrdMap::new()"));
+    }
+
+    #[test]
+    fn test_placeholder_tests_are_ignored() {
+        let code = generate(&spec(), [0; 32], None, None).unwrap();
+        assert!(code.contains("#[ignore = "));
+        assert!(code.contains("fn test_hello()"));
+    }
+}
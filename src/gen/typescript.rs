@@ -0,0 +1,814 @@
+use std::fmt::Write;
+
+use soroban_env_host::xdr::{
+    ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption,
+    ScSpecTypeResult, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+    ScSpecUdtStructV0, ScSpecUdtUnionV0,
+};
+
+const IMPORTS: &str =
+    "import { Account, Keypair, TimeoutInfinite, TransactionBuilder, xdr } from \"soroban-client\";\n";
+
+/// Names exported by `RUNTIME` that the other generated modules use.
+const RUNTIME_EXPORTS: &[&str] = &[
+    "Result",
+    "ClientOptions",
+    "InvokeOptions",
+    "scObject",
+    "isNone",
+    "scValToBool",
+    "bigIntToScVal",
+    "scValToBigInt",
+    "structField",
+    "contractIdBytes",
+    "rpc",
+];
+
+/// Helpers shared by the generated encoders, decoders and client.
+const RUNTIME: &str = r#"export type Result<T, E> = { ok: T } | { error: E };
+
+export interface ClientOptions {
+  /// Contract ID, as a C... strkey or 64 hex characters
+  contractId: string;
+  /// URL of the JSON-RPC endpoint
+  rpcUrl?: string;
+  networkPassphrase?: string;
+  /// Keypair that signs transactions, a random one is used if not set
+  keypair?: Keypair;
+}
+
+export interface InvokeOptions {
+  /// Submit the transaction with sendTransaction instead of only simulating it
+  send?: boolean;
+  /// Milliseconds to wait for a sent transaction to leave the pending status, 10 seconds if not
+  /// set
+  timeout?: number;
+}
+
+export function scObject(v: xdr.ScVal): xdr.ScObject {
+  const o = v.obj();
+  if (!o) {
+    throw new Error("expected an object value");
+  }
+  return o;
+}
+
+export function isNone(v: xdr.ScVal): boolean {
+  return v.switch().value === xdr.ScValType.scvObject().value && !v.obj();
+}
+
+export function scValToBool(v: xdr.ScVal): boolean {
+  return v.ic().value === xdr.ScStatic.scsTrue().value;
+}
+
+export function bigIntToScVal(v: bigint): xdr.ScVal {
+  if (v === 0n) {
+    return xdr.ScVal.scvObject(xdr.ScObject.scoBigInt(xdr.ScBigInt.zero()));
+  }
+  let hex = (v < 0n ? -v : v).toString(16);
+  if (hex.length % 2) {
+    hex = "0" + hex;
+  }
+  const magnitude = Buffer.from(hex, "hex");
+  return xdr.ScVal.scvObject(
+    xdr.ScObject.scoBigInt(v < 0n ? xdr.ScBigInt.negative(magnitude) : xdr.ScBigInt.positive(magnitude))
+  );
+}
+
+export function scValToBigInt(v: xdr.ScVal): bigint {
+  const b = scObject(v).bigInt();
+  if (b.switch().value === xdr.ScNumSign.zero().value) {
+    return 0n;
+  }
+  const magnitude = BigInt("0x" + Buffer.from(b.magnitude()).toString("hex"));
+  return b.switch().value === xdr.ScNumSign.negative().value ? -magnitude : magnitude;
+}
+
+export function structField(v: xdr.ScVal, name: string): xdr.ScVal {
+  const entry = scObject(v)
+    .map()
+    .find((e) => e.key().sym().toString() === name);
+  if (!entry) {
+    throw new Error(`missing field ${name}`);
+  }
+  return entry.val();
+}
+
+/// Decodes a contract ID given as 64 hex characters or as a C... strkey.
+export function contractIdBytes(id: string): Buffer {
+  if (/^[0-9a-fA-F]{64}$/.test(id)) {
+    return Buffer.from(id, "hex");
+  }
+  // A strkey is the version byte, the ID and a CRC16-XMODEM checksum, base32 encoded
+  const alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+  const bytes: number[] = [];
+  let buffer = 0;
+  let bits = 0;
+  for (const c of id) {
+    const v = alphabet.indexOf(c);
+    if (v < 0) {
+      break;
+    }
+    buffer = ((buffer << 5) | v) & 0xfff;
+    bits += 5;
+    if (bits >= 8) {
+      bits -= 8;
+      bytes.push((buffer >> bits) & 0xff);
+    }
+  }
+  let crc = 0;
+  for (const b of bytes.slice(0, 33)) {
+    crc ^= b << 8;
+    for (let i = 0; i < 8; i++) {
+      crc = (crc & 0x8000 ? (crc << 1) ^ 0x1021 : crc << 1) & 0xffff;
+    }
+  }
+  if (id.length !== 56 || bytes.length !== 35 || bytes[0] !== 2 << 3 || bytes[33] + (bytes[34] << 8) !== crc) {
+    throw new Error(`contract ID ${id} must be 64 hex characters or a C... strkey`);
+  }
+  return Buffer.from(bytes.slice(1, 33));
+}
+
+export async function rpc(url: string, method: string, params: unknown[]): Promise<any> {
+  const response = await fetch(url, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ jsonrpc: "2.0", id: 1, method, params }),
+  });
+  const body = await response.json();
+  if (body.error) {
+    throw new Error(`${method}: ${body.error.message}`);
+  }
+  return body.result;
+}
+"#;
+
+const CLIENT: &str = r#"
+export class Contract {
+  readonly contractId: string;
+  private readonly idBytes: Buffer;
+  readonly rpcUrl: string;
+  readonly networkPassphrase: string;
+  readonly keypair: Keypair;
+
+  constructor(options: ClientOptions) {
+    this.contractId = options.contractId;
+    this.idBytes = contractIdBytes(options.contractId);
+    this.rpcUrl = options.rpcUrl ?? "http://localhost:8080/api/v1/jsonrpc";
+    this.networkPassphrase = options.networkPassphrase ?? "Local Sandbox Stellar Network ; September 2022";
+    this.keypair = options.keypair ?? Keypair.random();
+  }
+
+  private transaction(params: xdr.ScVal[], footprint: xdr.LedgerFootprint): string {
+    const op = new xdr.Operation({
+      sourceAccount: null,
+      body: xdr.OperationBody.invokeHostFunction(
+        new xdr.InvokeHostFunctionOp({ function: xdr.HostFunction.hostFnCall(), parameters: params, footprint })
+      ),
+    });
+    // The sandbox doesn't check sequence numbers
+    const tx = new TransactionBuilder(new Account(this.keypair.publicKey(), "0"), {
+      fee: "100",
+      networkPassphrase: this.networkPassphrase,
+    })
+      .addOperation(op as any)
+      .setTimeout(TimeoutInfinite)
+      .build();
+    tx.sign(this.keypair);
+    return tx.toXDR();
+  }
+
+  private async invoke(method: string, args: xdr.ScVal[], options: InvokeOptions = {}): Promise<xdr.ScVal> {
+    const params = [
+      xdr.ScVal.scvObject(xdr.ScObject.scoBytes(this.idBytes)),
+      xdr.ScVal.scvSymbol(method),
+      ...args,
+    ];
+    const empty = new xdr.LedgerFootprint({ readOnly: [], readWrite: [] });
+    const simulated = await rpc(this.rpcUrl, "simulateTransaction", [this.transaction(params, empty)]);
+    if (!options.send) {
+      return xdr.ScVal.fromXDR(simulated.results[0].xdr, "base64");
+    }
+
+    const footprint = new xdr.LedgerFootprint({
+      readOnly: simulated.footprint.readOnly.map((k: string) => xdr.LedgerKey.fromXDR(k, "base64")),
+      readWrite: simulated.footprint.readWrite.map((k: string) => xdr.LedgerKey.fromXDR(k, "base64")),
+    });
+    const sent = await rpc(this.rpcUrl, "sendTransaction", [this.transaction(params, footprint)]);
+    let status = sent;
+    const deadline = Date.now() + (options.timeout ?? 10000);
+    while (status.status === "pending") {
+      if (Date.now() >= deadline) {
+        throw new Error(`${method}: transaction ${sent.id} is still pending`);
+      }
+      await new Promise((resolve) => setTimeout(resolve, 100));
+      status = await rpc(this.rpcUrl, "getTransactionStatus", [sent.id]);
+    }
+    if (status.status !== "success") {
+      throw new Error(`${method}: ${JSON.stringify(status.error)}`);
+    }
+    return xdr.ScVal.fromXDR(status.results[0].results[0].xdr, "base64");
+  }
+"#;
+
+/// Generates a TypeScript module with a type, encoder and decoder for each user defined type in
+/// the spec, and a `Contract` client with one async method per function.
+pub fn generate(spec: &[ScSpecEntry]) -> String {
+    let mut out = String::new();
+    out.push_str("// This file was generated by soroban-cli. Do not edit.\n\n");
+    out.push_str(IMPORTS);
+    out.push('\n');
+    out.push_str(RUNTIME);
+    generate_types(&mut out, spec);
+    generate_client(&mut out, spec);
+    out
+}
+
+/// Generates a TypeScript module with the helpers and the types shared by several contracts,
+/// for use by the modules generated by `generate_contract`.
+pub fn generate_shared(shared: &[ScSpecEntry]) -> String {
+    let mut out = String::new();
+    out.push_str("// This file was generated by soroban-cli. Do not edit.\n\n");
+    out.push_str(IMPORTS);
+    out.push('\n');
+    out.push_str(RUNTIME);
+    generate_types(&mut out, shared);
+    out
+}
+
+/// Generates a TypeScript module for one contract, importing the helpers and shared types from
+/// the `types` module generated by `generate_shared`.
+pub fn generate_contract(spec: &[ScSpecEntry], shared: &[ScSpecEntry]) -> String {
+    let mut imports = RUNTIME_EXPORTS
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    for entry in shared {
+        let name = match entry {
+            ScSpecEntry::UdtStructV0(s) => s.name.to_string_lossy(),
+            ScSpecEntry::UdtUnionV0(u) => u.name.to_string_lossy(),
+            ScSpecEntry::FunctionV0(_) => continue,
+        };
+        imports.push(format!("encode{}", name));
+        imports.push(format!("decode{}", name));
+        imports.push(name);
+    }
+
+    let mut out = String::new();
+    out.push_str("// This file was generated by soroban-cli. Do not edit.\n\n");
+    out.push_str(IMPORTS);
+    writeln!(out, "import {{ {} }} from \"./types\";", imports.join(", ")).unwrap();
+    generate_types(&mut out, spec);
+    generate_client(&mut out, spec);
+    out
+}
+
+fn generate_types(out: &mut String, spec: &[ScSpecEntry]) {
+    for entry in spec {
+        match entry {
+            ScSpecEntry::UdtStructV0(s) => generate_struct(out, s),
+            ScSpecEntry::UdtUnionV0(u) => generate_union(out, u),
+            ScSpecEntry::FunctionV0(_) => {}
+        }
+    }
+}
+
+fn generate_client(out: &mut String, spec: &[ScSpecEntry]) {
+    out.push_str(CLIENT);
+    for entry in spec {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            generate_function(out, f);
+        }
+    }
+    out.push_str("}\n");
+}
+
+fn generate_struct(out: &mut String, s: &ScSpecUdtStructV0) {
+    let name = s.name.to_string_lossy();
+    writeln!(out, "\nexport interface {} {{", name).unwrap();
+    for f in s.fields.iter() {
+        writeln!(
+            out,
+            "  {}: {};",
+            f.name.to_string_lossy(),
+            type_to_ts(&f.type_)
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+
+    // Structs are encoded as a map keyed by field name, and maps must be sorted by key
+    let mut fields = s.fields.iter().collect::<Vec<_>>();
+    fields.sort_by_key(|f| f.name.to_string_lossy());
+    writeln!(
+        out,
+        "\nexport function encode{}(v: {}): xdr.ScVal {{",
+        name, name
+    )
+    .unwrap();
+    out.push_str("  return xdr.ScVal.scvObject(\n    xdr.ScObject.scoMap([\n");
+    for f in &fields {
+        let field = f.name.to_string_lossy();
+        writeln!(
+            out,
+            "      new xdr.ScMapEntry({{ key: xdr.ScVal.scvSymbol(\"{}\"), val: {} }}),",
+            field,
+            encode(&f.type_, &format!("v.{}", field))
+        )
+        .unwrap();
+    }
+    out.push_str("    ])\n  );\n}\n");
+
+    writeln!(
+        out,
+        "\nexport function decode{}(v: xdr.ScVal): {} {{",
+        name, name
+    )
+    .unwrap();
+    out.push_str("  return {\n");
+    for f in s.fields.iter() {
+        let field = f.name.to_string_lossy();
+        writeln!(
+            out,
+            "    {}: {},",
+            field,
+            decode(&f.type_, &format!("structField(v, \"{}\")", field))
+        )
+        .unwrap();
+    }
+    out.push_str("  };\n}\n");
+}
+
+fn generate_union(out: &mut String, u: &ScSpecUdtUnionV0) {
+    let name = u.name.to_string_lossy();
+    let cases = u
+        .cases
+        .iter()
+        .map(|c| match &c.type_ {
+            Some(t) => format!(
+                "{{ tag: \"{}\"; value: {} }}",
+                c.name.to_string_lossy(),
+                type_to_ts(t)
+            ),
+            None => format!("{{ tag: \"{}\" }}", c.name.to_string_lossy()),
+        })
+        .collect::<Vec<_>>();
+    writeln!(out, "\nexport type {} = {};", name, cases.join(" | ")).unwrap();
+
+    // Unions are encoded as a vec of the case name followed by the value, if any
+    writeln!(
+        out,
+        "\nexport function encode{}(v: {}): xdr.ScVal {{",
+        name, name
+    )
+    .unwrap();
+    out.push_str("  switch (v.tag) {\n");
+    for c in u.cases.iter() {
+        let case = c.name.to_string_lossy();
+        let value = c
+            .type_
+            .as_ref()
+            .map(|t| format!(", {}", encode(t, "v.value")))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "    case \"{}\":\n      return xdr.ScVal.scvObject(xdr.ScObject.scoVec([xdr.ScVal.scvSymbol(\"{}\"){}]));",
+            case, case, value
+        )
+        .unwrap();
+    }
+    out.push_str("  }\n}\n");
+
+    writeln!(
+        out,
+        "\nexport function decode{}(v: xdr.ScVal): {} {{",
+        name, name
+    )
+    .unwrap();
+    out.push_str("  const vec = scObject(v).vec();\n  switch (vec[0].sym().toString()) {\n");
+    for c in u.cases.iter() {
+        let case = c.name.to_string_lossy();
+        match &c.type_ {
+            Some(t) => writeln!(
+                out,
+                "    case \"{}\":\n      return {{ tag: \"{}\", value: {} }};",
+                case,
+                case,
+                decode(t, "vec[1]")
+            ),
+            None => writeln!(
+                out,
+                "    case \"{}\":\n      return {{ tag: \"{}\" }};",
+                case, case
+            ),
+        }
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "    default:\n      throw new Error(`unknown {} case ${{vec[0].sym()}}`);\n  }}\n}}",
+        name
+    )
+    .unwrap();
+}
+
+fn generate_function(out: &mut String, f: &ScSpecFunctionV0) {
+    let name = f.name.to_string_lossy();
+    let params = f
+        .inputs
+        .iter()
+        .map(|i| format!("{}: {}", i.name.to_string_lossy(), type_to_ts(&i.type_)))
+        .collect::<Vec<_>>();
+    let args = f
+        .inputs
+        .iter()
+        .map(|i| encode(&i.type_, &i.name.to_string_lossy()))
+        .collect::<Vec<_>>();
+    let (ret, result) = match f.outputs.as_slice() {
+        [] => ("void".to_string(), None),
+        [t] => (type_to_ts(t), Some(decode(t, "result"))),
+        outputs => {
+            let tuple = ScSpecTypeDef::Tuple(Box::new(ScSpecTypeTuple {
+                value_types: outputs.to_vec().try_into().unwrap_or_default(),
+            }));
+            (type_to_ts(&tuple), Some(decode(&tuple, "result")))
+        }
+    };
+
+    let mut signature = params.join(", ");
+    if !signature.is_empty() {
+        signature.push_str(", ");
+    }
+    writeln!(
+        out,
+        "\n  async {}({}options?: InvokeOptions): Promise<{}> {{",
+        name, signature, ret
+    )
+    .unwrap();
+    let call = format!("this.invoke(\"{}\", [{}], options)", name, args.join(", "));
+    match result {
+        Some(result) => {
+            writeln!(out, "    const result = await {};", call).unwrap();
+            writeln!(out, "    return {};", result).unwrap();
+        }
+        None => writeln!(out, "    await {};", call).unwrap(),
+    }
+    out.push_str("  }\n");
+}
+
+/// The TypeScript type a spec type is represented as. 64 bit and larger integers use `bigint`
+/// because they don't fit in a `number`.
+pub fn type_to_ts(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::U32 | ScSpecTypeDef::I32 => "number".to_string(),
+        ScSpecTypeDef::U64 | ScSpecTypeDef::I64 | ScSpecTypeDef::BigInt | ScSpecTypeDef::Bitset => {
+            "bigint".to_string()
+        }
+        ScSpecTypeDef::Bool => "boolean".to_string(),
+        ScSpecTypeDef::Symbol => "string".to_string(),
+        ScSpecTypeDef::Bytes => "Uint8Array".to_string(),
+        ScSpecTypeDef::Status => "xdr.ScStatus".to_string(),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!("{} | undefined", type_to_ts(value_type))
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult {
+                ok_type,
+                error_type,
+            } = &**r;
+            format!(
+                "Result<{}, {}>",
+                type_to_ts(ok_type),
+                type_to_ts(error_type)
+            )
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!("Array<{}>", type_to_ts(element_type))
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!("Array<{}>", type_to_ts(element_type))
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            format!("Map<{}, {}>", type_to_ts(key_type), type_to_ts(value_type))
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let types = value_types.iter().map(type_to_ts).collect::<Vec<_>>();
+            format!("[{}]", types.join(", "))
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => name.to_string_lossy(),
+    }
+}
+
+/// A TypeScript expression that encodes the value of `expr` to an `xdr.ScVal`, following the
+/// same conventions as `strval`.
+fn encode(t: &ScSpecTypeDef, expr: &str) -> String {
+    match t {
+        ScSpecTypeDef::U32 => format!("xdr.ScVal.scvU32({})", expr),
+        ScSpecTypeDef::I32 => format!("xdr.ScVal.scvI32({})", expr),
+        ScSpecTypeDef::U64 => format!(
+            "xdr.ScVal.scvU63(xdr.Int64.fromString({}.toString()))",
+            expr
+        ),
+        ScSpecTypeDef::I64 => format!(
+            "xdr.ScVal.scvObject(xdr.ScObject.scoI64(xdr.Int64.fromString({}.toString())))",
+            expr
+        ),
+        ScSpecTypeDef::BigInt => format!("bigIntToScVal({})", expr),
+        ScSpecTypeDef::Bitset => format!(
+            "xdr.ScVal.scvBitset(xdr.Uint64.fromString({}.toString()))",
+            expr
+        ),
+        ScSpecTypeDef::Status => format!("xdr.ScVal.scvStatus({})", expr),
+        ScSpecTypeDef::Bool => format!(
+            "xdr.ScVal.scvStatic({} ? xdr.ScStatic.scsTrue() : xdr.ScStatic.scsFalse())",
+            expr
+        ),
+        ScSpecTypeDef::Symbol => format!("xdr.ScVal.scvSymbol({})", expr),
+        ScSpecTypeDef::Bytes => format!(
+            "xdr.ScVal.scvObject(xdr.ScObject.scoBytes(Buffer.from({})))",
+            expr
+        ),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!(
+                "((v) => (v === undefined ? xdr.ScVal.scvObject(null) : {}))({})",
+                encode(value_type, "v"),
+                expr
+            )
+        }
+        ScSpecTypeDef::Result(_) => {
+            "(() => { throw new Error(\"a Result cannot be passed as an argument\"); })()"
+                .to_string()
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!(
+                "xdr.ScVal.scvObject(xdr.ScObject.scoVec({}.map((v) => {})))",
+                expr,
+                encode(element_type, "v")
+            )
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!(
+                "xdr.ScVal.scvObject(xdr.ScObject.scoVec({}.map((v) => {})))",
+                expr,
+                encode(element_type, "v")
+            )
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            // Maps must be sorted by key, like the fields of an encoded struct
+            format!(
+                "xdr.ScVal.scvObject(xdr.ScObject.scoMap(Array.from({}.entries()).map(([k, v]) => new xdr.ScMapEntry({{ key: {}, val: {} }})).sort({})))",
+                expr,
+                encode(key_type, "k"),
+                encode(value_type, "v"),
+                compare_keys(key_type)
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let values = value_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| encode(t, &format!("v[{}]", i)))
+                .collect::<Vec<_>>();
+            format!(
+                "((v) => xdr.ScVal.scvObject(xdr.ScObject.scoVec([{}])))({})",
+                values.join(", "),
+                expr
+            )
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            format!("encode{}({})", name.to_string_lossy(), expr)
+        }
+    }
+}
+
+/// A TypeScript comparator of two `xdr.ScMapEntry`s that orders them by key the way the host
+/// orders map keys. Keys of types whose decoded values don't order the same way are compared by
+/// their XDR.
+fn compare_keys(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::U32
+        | ScSpecTypeDef::I32
+        | ScSpecTypeDef::U64
+        | ScSpecTypeDef::I64
+        | ScSpecTypeDef::BigInt
+        | ScSpecTypeDef::Bitset
+        | ScSpecTypeDef::Bool
+        | ScSpecTypeDef::Symbol => format!(
+            "(a, b) => {{ const x = {}, y = {}; return x < y ? -1 : x > y ? 1 : 0; }}",
+            decode(t, "a.key()"),
+            decode(t, "b.key()")
+        ),
+        ScSpecTypeDef::Bytes => {
+            "(a, b) => Buffer.compare(scObject(a.key()).bin(), scObject(b.key()).bin())".to_string()
+        }
+        _ => "(a, b) => Buffer.compare(a.key().toXDR(), b.key().toXDR())".to_string(),
+    }
+}
+
+/// A TypeScript expression that decodes the `xdr.ScVal` in `expr` to the type's TypeScript
+/// representation.
+fn decode(t: &ScSpecTypeDef, expr: &str) -> String {
+    match t {
+        ScSpecTypeDef::U32 => format!("{}.u32()", expr),
+        ScSpecTypeDef::I32 => format!("{}.i32()", expr),
+        ScSpecTypeDef::U64 => format!("BigInt({}.u63().toString())", expr),
+        ScSpecTypeDef::I64 => format!("BigInt(scObject({}).i64().toString())", expr),
+        ScSpecTypeDef::BigInt => format!("scValToBigInt({})", expr),
+        ScSpecTypeDef::Bitset => format!("BigInt({}.bits().toString())", expr),
+        ScSpecTypeDef::Status => format!("{}.status()", expr),
+        ScSpecTypeDef::Bool => format!("scValToBool({})", expr),
+        ScSpecTypeDef::Symbol => format!("{}.sym().toString()", expr),
+        ScSpecTypeDef::Bytes => format!("new Uint8Array(scObject({}).bin())", expr),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = &**o;
+            format!(
+                "((v) => (isNone(v) ? undefined : {}))({})",
+                decode(value_type, "v"),
+                expr
+            )
+        }
+        // Errors are returned by the host as a failed invocation, so a returned value is always
+        // the ok value
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult { ok_type, .. } = &**r;
+            format!("{{ ok: {} }}", decode(ok_type, expr))
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = &**v;
+            format!(
+                "scObject({}).vec().map((v) => {})",
+                expr,
+                decode(element_type, "v")
+            )
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = &**s;
+            format!(
+                "scObject({}).vec().map((v) => {})",
+                expr,
+                decode(element_type, "v")
+            )
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**m;
+            format!(
+                "new Map(scObject({}).map().map((e) => [{}, {}]))",
+                expr,
+                decode(key_type, "e.key()"),
+                decode(value_type, "e.val()")
+            )
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = &**t;
+            let values = value_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| decode(t, &format!("v[{}]", i)))
+                .collect::<Vec<_>>();
+            format!(
+                "((v) => [{}] as [{}])(scObject({}).vec())",
+                values.join(", "),
+                value_types
+                    .iter()
+                    .map(type_to_ts)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                expr
+            )
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            format!("decode{}({})", name.to_string_lossy(), expr)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_env_host::xdr::{
+        ScSpecFunctionInputV0, ScSpecUdtStructFieldV0, ScSpecUdtUnionCaseV0,
+    };
+
+    fn spec() -> Vec<ScSpecEntry> {
+        let udt = |name: &str| {
+            ScSpecTypeDef::Udt(ScSpecTypeUdt {
+                name: name.as_bytes().try_into().unwrap(),
+            })
+        };
+        vec![
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                name: "Config".as_bytes().try_into().unwrap(),
+                fields: vec![
+                    ScSpecUdtStructFieldV0 {
+                        name: "limit".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::U32,
+                    },
+                    ScSpecUdtStructFieldV0 {
+                        name: "admin".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::Symbol,
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+                name: "Key".as_bytes().try_into().unwrap(),
+                cases: vec![
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Admin".as_bytes().try_into().unwrap(),
+                        type_: None,
+                    },
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Balance".as_bytes().try_into().unwrap(),
+                        type_: Some(ScSpecTypeDef::U32),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+                name: "set".as_bytes().try_into().unwrap(),
+                inputs: [
+                    ("config", udt("Config")),
+                    ("key", udt("Key")),
+                    (
+                        "weights",
+                        ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+                            key_type: ScSpecTypeDef::Symbol,
+                            value_type: ScSpecTypeDef::U32,
+                        })),
+                    ),
+                ]
+                .into_iter()
+                .map(|(name, type_)| ScSpecFunctionInputV0 {
+                    name: name.as_bytes().try_into().unwrap(),
+                    type_,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+                outputs: vec![ScSpecTypeDef::U32].try_into().unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_struct() {
+        let out = generate(&spec());
+        assert!(out.contains("export interface Config {\n  limit: number;\n  admin: string;\n}\n"));
+        // Fields are encoded sorted by name, and decoded by name
+        let encoder = &out[out.find("export function encodeConfig").unwrap()..];
+        let admin =
+            encoder.find("xdr.ScVal.scvSymbol(\"admin\"), val: xdr.ScVal.scvSymbol(v.admin)");
+        let limit = encoder.find("xdr.ScVal.scvSymbol(\"limit\"), val: xdr.ScVal.scvU32(v.limit)");
+        assert!(admin.unwrap() < limit.unwrap());
+        assert!(out.contains("    limit: structField(v, \"limit\").u32(),\n"));
+    }
+
+    #[test]
+    fn test_union() {
+        let out = generate(&spec());
+        assert!(out.contains(
+            "export type Key = { tag: \"Admin\" } | { tag: \"Balance\"; value: number };"
+        ));
+        assert!(out.contains(
+            "    case \"Balance\":\n      return xdr.ScVal.scvObject(xdr.ScObject.scoVec([xdr.ScVal.scvSymbol(\"Balance\"), xdr.ScVal.scvU32(v.value)]));"
+        ));
+        assert!(out.contains("    case \"Admin\":\n      return { tag: \"Admin\" };"));
+    }
+
+    #[test]
+    fn test_function() {
+        let out = generate(&spec());
+        assert!(out.contains(
+            "  async set(config: Config, key: Key, weights: Map<string, number>, options?: InvokeOptions): Promise<number> {"
+        ));
+        assert!(out.contains(
+            "    const result = await this.invoke(\"set\", [encodeConfig(config), encodeKey(key), "
+        ));
+        assert!(out.contains("    return result.u32();\n"));
+        // Map entries are sorted by key
+        assert!(out.contains(
+            ".sort((a, b) => { const x = a.key().sym().toString(), y = b.key().sym().toString(); return x < y ? -1 : x > y ? 1 : 0; })"
+        ));
+    }
+}
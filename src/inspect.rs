@@ -1,15 +1,30 @@
-use clap::Parser;
+use clap::{ArgEnum, Parser};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use soroban_env_host::{
-    xdr::{self, ReadXdr, ScEnvMetaEntry, ScSpecEntry},
+    xdr::{self, ScEnvMetaEntry, ScSpecEntry},
     HostError,
 };
-use std::{fmt::Debug, fs, io, io::Cursor, str::Utf8Error};
+use std::{collections::BTreeMap, fmt::Debug, io, str::Utf8Error};
+
+use crate::contractspec;
+use crate::wasm;
 
 #[derive(Parser, Debug)]
 pub struct Cmd {
-    /// WASM file to inspect
-    #[clap(long, parse(from_os_str))]
-    wasm: std::path::PathBuf,
+    #[clap(flatten)]
+    wasm: wasm::Args,
+    /// Type of output to generate
+    #[clap(long, arg_enum, default_value = "text")]
+    output: Output,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Output {
+    /// Human readable listing of the contract
+    Text,
+    /// Json document describing the contract
+    Json,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -24,33 +39,29 @@ pub enum Error {
     Host(#[from] HostError),
     #[error("utf8")]
     Utf8Error(#[from] Utf8Error),
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
 }
 
 impl Cmd {
     pub fn run(&self) -> Result<(), Error> {
-        println!("File: {}", self.wasm.to_string_lossy());
-
-        let contents = fs::read(&self.wasm)?;
-
-        let mut env_meta: Option<&[u8]> = None;
-        let mut spec: Option<&[u8]> = None;
-        for payload in wasmparser::Parser::new(0).parse_all(&contents) {
-            let payload = payload.map_err(Error::WasmParse)?;
-            if let wasmparser::Payload::CustomSection(section) = payload {
-                let out = match section.name() {
-                    "contractenvmetav0" => &mut env_meta,
-                    "contractspecv0" => &mut spec,
-                    _ => continue,
-                };
-                *out = Some(section.data());
-            };
+        let contents = self.wasm.read()?;
+        let module = wasm::parse(&contents).map_err(Error::WasmParse)?;
+        match self.output {
+            Output::Text => self.print_text(&module),
+            Output::Json => self.print_json(&contents, &module),
         }
+    }
+
+    fn print_text(&self, module: &wasm::Module) -> Result<(), Error> {
+        println!("File: {}", self.wasm.source());
 
-        if let Some(env_meta) = env_meta {
+        if let Some(env_meta) = module.env_meta {
             println!("Env Meta: {}", base64::encode(env_meta));
-            let mut cursor = Cursor::new(env_meta);
-            for env_meta_entry in ScEnvMetaEntry::read_xdr_iter(&mut cursor) {
-                match env_meta_entry? {
+            for env_meta_entry in module.env_meta_entries()? {
+                match env_meta_entry {
                     ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(v) => {
                         println!(" • Interface Version: {}", v);
                     }
@@ -60,28 +71,245 @@ impl Cmd {
             println!("Env Meta: None");
         }
 
-        if let Some(spec) = spec {
+        if let Some(spec) = module.spec {
             println!("Contract Spec: {}", base64::encode(spec));
-            let mut cursor = Cursor::new(spec);
-            for spec_entry in ScSpecEntry::read_xdr_iter(&mut cursor) {
-                match spec_entry? {
-                    ScSpecEntry::FunctionV0(f) => println!(
-                        " • Function: {} ({:?}) -> ({:?})",
-                        f.name.to_string()?,
-                        f.inputs.as_slice(),
-                        f.outputs.as_slice(),
-                    ),
+            for spec_entry in module.spec_entries()? {
+                match spec_entry {
+                    ScSpecEntry::FunctionV0(f) => {
+                        println!(" • Function: {}", contractspec::function_signature(&f));
+                    }
                     ScSpecEntry::UdtUnionV0(udt) => {
-                        println!(" • Union: {:?}", udt);
+                        println!(" • Union: {}", contractspec::union_definition(&udt));
                     }
                     ScSpecEntry::UdtStructV0(udt) => {
-                        println!(" • Struct: {:?}", udt);
+                        println!(" • Struct: {}", contractspec::struct_definition(&udt));
                     }
                 }
             }
         } else {
             println!("Contract Spec: None");
         }
+
+        println!("Exports:");
+        for export in &module.exports {
+            println!(" • {:?}: {}", export.kind, export.name);
+        }
+        println!("Imports:");
+        for (import_module, names) in imports_by_module(module) {
+            println!(" • {}: {}", import_module, names.join(", "));
+        }
+        for memory in &module.memories {
+            println!(
+                "Memory: {} pages initial, {} maximum",
+                memory.initial,
+                memory.maximum.map_or("no".to_string(), |m| m.to_string())
+            );
+        }
+        for table in &module.tables {
+            println!(
+                "Table: {} elements initial, {} maximum",
+                table.initial,
+                table.maximum.map_or("no".to_string(), |m| m.to_string())
+            );
+        }
+        println!("Custom Sections:");
+        for (name, size) in &module.custom_sections {
+            println!(" • {}: {} bytes", name, size);
+        }
+        println!("Function Sizes:");
+        for (index, name, size) in function_sizes(module) {
+            println!(
+                " • {} ({}): {} bytes",
+                index,
+                name.unwrap_or("not exported"),
+                size
+            );
+        }
+
+        let (not_exported, not_in_spec) = spec_mismatches(module)?;
+        for name in not_exported {
+            eprintln!(
+                "warning: function {} is in the contract spec but is not exported",
+                name
+            );
+        }
+        for name in not_in_spec {
+            eprintln!(
+                "warning: function {} is exported but is not in the contract spec",
+                name
+            );
+        }
+        Ok(())
+    }
+
+    fn print_json(&self, contents: &[u8], module: &wasm::Module) -> Result<(), Error> {
+        let env_meta = module
+            .env_meta_entries()?
+            .into_iter()
+            .map(|e| match e {
+                ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(v) => {
+                    json!({ "interfaceVersion": v })
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut functions = vec![];
+        let mut structs = vec![];
+        let mut unions = vec![];
+        for spec_entry in module.spec_entries()? {
+            match spec_entry {
+                ScSpecEntry::FunctionV0(f) => {
+                    let inputs = f
+                        .inputs
+                        .iter()
+                        .map(|i| {
+                            Ok(json!({
+                                "name": i.name.to_string()?,
+                                "type": serde_json::to_value(&i.type_)?,
+                            }))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    functions.push(json!({
+                        "name": f.name.to_string()?,
+                        "signature": contractspec::function_signature(&f),
+                        "inputs": inputs,
+                        "outputs": serde_json::to_value(f.outputs.as_slice())?,
+                    }));
+                }
+                ScSpecEntry::UdtStructV0(s) => {
+                    let fields = s
+                        .fields
+                        .iter()
+                        .map(|f| {
+                            Ok(json!({
+                                "name": f.name.to_string()?,
+                                "type": serde_json::to_value(&f.type_)?,
+                            }))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    structs.push(json!({ "name": s.name.to_string()?, "fields": fields }));
+                }
+                ScSpecEntry::UdtUnionV0(u) => {
+                    let cases = u
+                        .cases
+                        .iter()
+                        .map(|c| {
+                            Ok(json!({
+                                "name": c.name.to_string()?,
+                                "type": serde_json::to_value(&c.type_)?,
+                            }))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    unions.push(json!({ "name": u.name.to_string()?, "cases": cases }));
+                }
+            }
+        }
+
+        let (not_exported, not_in_spec) = spec_mismatches(module)?;
+        let exports = module
+            .exports
+            .iter()
+            .map(|e| json!({ "name": e.name, "kind": format!("{:?}", e.kind) }))
+            .collect::<Vec<_>>();
+        let memories = module
+            .memories
+            .iter()
+            .map(|m| json!({ "initial": m.initial, "maximum": m.maximum }))
+            .collect::<Vec<_>>();
+        let tables = module
+            .tables
+            .iter()
+            .map(|t| json!({ "initial": t.initial, "maximum": t.maximum }))
+            .collect::<Vec<_>>();
+        let custom_sections = module
+            .custom_sections
+            .iter()
+            .map(|(name, size)| json!({ "name": name, "size": size }))
+            .collect::<Vec<_>>();
+        let function_sizes = function_sizes(module)
+            .into_iter()
+            .map(|(index, name, size)| json!({ "index": index, "export": name, "size": size }))
+            .collect::<Vec<_>>();
+
+        let doc: Value = json!({
+            "file": self.wasm.source(),
+            "size": contents.len(),
+            "sha256": hex::encode(Sha256::digest(contents)),
+            "envMeta": env_meta,
+            "functions": functions,
+            "structs": structs,
+            "unions": unions,
+            "sections": {
+                "contractenvmetav0": module.env_meta.map(base64::encode),
+                "contractspecv0": module.spec.map(base64::encode),
+            },
+            "module": {
+                "exports": exports,
+                "imports": imports_by_module(module),
+                "memories": memories,
+                "tables": tables,
+                "customSections": custom_sections,
+                "functionSizes": function_sizes,
+            },
+            "specNotExported": not_exported,
+            "exportedNotInSpec": not_in_spec,
+        });
+        println!("{}", serde_json::to_string_pretty(&doc)?);
         Ok(())
     }
 }
+
+/// Imported function names, grouped by the host module they are imported from.
+fn imports_by_module<'a>(module: &wasm::Module<'a>) -> BTreeMap<&'a str, Vec<&'a str>> {
+    let mut imports: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for import in &module.imports {
+        if let wasmparser::TypeRef::Func(_) = import.ty {
+            imports.entry(import.module).or_default().push(import.name);
+        }
+    }
+    imports
+}
+
+/// The function index, export name if any, and body size of each function defined in the module.
+fn function_sizes<'a>(module: &wasm::Module<'a>) -> Vec<(usize, Option<&'a str>, usize)> {
+    let first = module.function_imports_count();
+    module
+        .function_body_sizes
+        .iter()
+        .enumerate()
+        .map(|(i, size)| {
+            let index = first + i;
+            let name = module
+                .exports
+                .iter()
+                .find(|e| {
+                    e.kind == wasmparser::ExternalKind::Func
+                        && usize::try_from(e.index).ok() == Some(index)
+                })
+                .map(|e| e.name);
+            (index, name, *size)
+        })
+        .collect()
+}
+
+/// Functions declared in the spec that aren't exported, and exported functions that aren't
+/// declared in the spec.
+fn spec_mismatches<'a>(module: &wasm::Module<'a>) -> Result<(Vec<String>, Vec<&'a str>), Error> {
+    let exports = module.function_exports();
+    let mut spec_functions = vec![];
+    for entry in module.spec_entries()? {
+        if let ScSpecEntry::FunctionV0(f) = entry {
+            spec_functions.push(f.name.to_string()?);
+        }
+    }
+    let not_exported = spec_functions
+        .iter()
+        .filter(|f| !exports.contains(&f.as_str()))
+        .cloned()
+        .collect();
+    let not_in_spec = exports
+        .into_iter()
+        .filter(|e| !spec_functions.iter().any(|f| f == e))
+        .collect();
+    Ok((not_exported, not_in_spec))
+}
@@ -5,14 +5,12 @@ use soroban_env_host::{
     budget::{Budget, CostType},
     storage::Storage,
     xdr::{
-        Error as XdrError, HostFunction, ReadXdr, ScHostStorageErrorCode, ScObject,
-        ScSpecFunctionInputV0, ScStatus, ScVal, VecM,
+        Error as XdrError, HostFunction, ReadXdr, ScHostStorageErrorCode, ScObject, ScSpecEntry,
+        ScSpecFunctionV0, ScStatus, ScVal,
     },
     Host, HostError, Vm,
 };
 
-use hex::FromHexError;
-
 use crate::contractspec;
 use crate::snapshot;
 use crate::strval::{self, StrValError};
@@ -27,8 +25,11 @@ pub struct Cmd {
     #[clap(long, parse(from_os_str))]
     wasm: Option<std::path::PathBuf>,
     /// Function name to execute
-    #[clap(long = "fn")]
-    function: String,
+    #[clap(long = "fn", required_unless_present = "list-functions")]
+    function: Option<String>,
+    /// List the function signatures of the contract instead of invoking a function
+    #[clap(long, conflicts_with_all = &["function", "args", "args-xdr"])]
+    list_functions: bool,
     /// Argument to pass to the function
     #[clap(long = "arg", value_name = "arg", multiple = true)]
     args: Vec<String>,
@@ -41,6 +42,9 @@ pub struct Cmd {
     /// File to persist ledger state
     #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
     ledger_file: std::path::PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -70,13 +74,12 @@ pub enum Error {
         filepath: std::path::PathBuf,
         error: snapshot::Error,
     },
-    #[error("cannot parse contract ID {contract_id}: {error}")]
-    CannotParseContractID {
-        contract_id: String,
-        error: FromHexError,
-    },
-    #[error("function {0} was not found in the contract")]
-    FunctionNotFoundInContractSpec(String),
+    #[error(transparent)]
+    CannotParseContractID(#[from] utils::ContractIdError),
+    #[error(
+        "function {function} was not found in the contract, available functions:\n{available}"
+    )]
+    FunctionNotFoundInContractSpec { function: String, available: String },
     #[error("unexpected number of arguments: {provided} (function {function} expects {expected} argument(s))")]
     UnexpectedArgumentCount {
         provided: usize,
@@ -101,8 +104,10 @@ impl Cmd {
     fn parse_args(
         &self,
         matches: &clap::ArgMatches,
-        inputs: &VecM<ScSpecFunctionInputV0, 10>,
+        spec: &ScSpecFunctionV0,
+        entries: &[ScSpecEntry],
     ) -> Result<Vec<ScVal>, Error> {
+        let inputs = &spec.inputs;
         // re-assemble the args, to match the order given on the command line
         let indexed_args: Vec<(usize, Arg)> = matches
             .indices_of("args")
@@ -123,7 +128,7 @@ impl Cmd {
             return Err(Error::UnexpectedArgumentCount {
                 provided: all_indexed_args.len(),
                 expected: inputs.len(),
-                function: self.function.clone(),
+                function: contractspec::function_signature(spec),
             });
         }
 
@@ -137,24 +142,19 @@ impl Cmd {
                         error: e,
                     })
                 }
-                Arg::Arg(s) => {
-                    strval::from_string(s, &input.type_).map_err(|e| Error::CannotParseArg {
+                Arg::Arg(s) => strval::from_string(s, &input.type_, entries).map_err(|e| {
+                    Error::CannotParseArg {
                         arg: s.clone(),
                         error: e,
-                    })
-                }
+                    }
+                }),
             })
             .collect::<Result<Vec<_>, _>>()
     }
 
     pub fn run(&self, matches: &clap::ArgMatches) -> Result<(), Error> {
         let contract_id: [u8; 32] =
-            utils::contract_id_from_str(&self.contract_id).map_err(|e| {
-                Error::CannotParseContractID {
-                    contract_id: self.contract_id.clone(),
-                    error: e,
-                }
-            })?;
+            utils::contract_id_from_str(&self.contract_id, self.allow_short_id)?;
 
         // Initialize storage and host
         // TODO: allow option to separate input and output file
@@ -182,21 +182,39 @@ impl Cmd {
         let h = Host::with_storage_and_budget(storage, Budget::default());
 
         let vm = Vm::new(&h, contract_id.into(), &contents)?;
-        let inputs = match contractspec::function_spec(&vm, &self.function) {
-            Some(s) => s.inputs,
+        // --fn is only left out with --list-functions
+        let function = match &self.function {
+            Some(function) => function,
             None => {
-                return Err(Error::FunctionNotFoundInContractSpec(self.function.clone()));
+                for f in contractspec::function_specs(&vm) {
+                    println!("{}", contractspec::function_signature(&f));
+                }
+                return Ok(());
+            }
+        };
+        let spec = match contractspec::function_spec(&vm, function) {
+            Some(s) => s,
+            None => {
+                let available = contractspec::function_specs(&vm)
+                    .iter()
+                    .map(|f| format!("  {}", contractspec::function_signature(f)))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Err(Error::FunctionNotFoundInContractSpec {
+                    function: function.clone(),
+                    available,
+                });
             }
         };
 
-        let parsed_args = self.parse_args(matches, &inputs)?;
+        let parsed_args = self.parse_args(matches, &spec, &contractspec::spec_entries(&vm))?;
 
         let mut complete_args = vec![
             ScVal::Object(Some(ScObject::Bytes(contract_id.try_into().unwrap()))),
             ScVal::Symbol(
-                (&self.function)
+                function
                     .try_into()
-                    .map_err(|_| Error::FunctionNameTooLong(self.function.clone()))?,
+                    .map_err(|_| Error::FunctionNameTooLong(function.clone()))?,
             ),
         ];
         complete_args.extend_from_slice(parsed_args.as_slice());
@@ -239,3 +257,19 @@ impl Cmd {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_list_functions_instead_of_fn() {
+        assert!(Cmd::try_parse_from(["invoke", "--id", "1", "--list-functions"]).is_ok());
+        assert!(Cmd::try_parse_from(["invoke", "--id", "1", "--fn", "hello"]).is_ok());
+        assert!(Cmd::try_parse_from(["invoke", "--id", "1"]).is_err());
+        assert!(
+            Cmd::try_parse_from(["invoke", "--id", "1", "--list-functions", "--fn", "hello"])
+                .is_err()
+        );
+    }
+}
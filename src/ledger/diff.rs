@@ -0,0 +1,274 @@
+use std::{collections::BTreeMap, fmt::Debug, path::PathBuf};
+
+use clap::{ArgEnum, Parser};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use soroban_env_host::{
+    im_rc::OrdMap,
+    xdr::{
+        Error as XdrError, LedgerEntry, LedgerEntryData, LedgerKey, ScContractCode, ScObject,
+        ScStatic, ScVal, WriteXdr,
+    },
+};
+
+use crate::snapshot;
+use crate::strval::{self, StrValError};
+use crate::utils;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Ledger snapshot file to compare from
+    #[clap(parse(from_os_str))]
+    before: PathBuf,
+    /// Ledger snapshot file to compare to
+    #[clap(parse(from_os_str))]
+    after: PathBuf,
+    /// Type of output to generate
+    #[clap(long, arg_enum, default_value = "text")]
+    output: Output,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Output {
+    /// Human readable listing of the changes
+    Text,
+    /// Json document of the changes
+    Json,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("ledger file {0} does not exist")]
+    FileNotFound(PathBuf),
+    #[error("reading file {filepath}: {error}")]
+    CannotReadLedgerFile {
+        filepath: PathBuf,
+        error: snapshot::Error,
+    },
+    #[error("xdr")]
+    Xdr(#[from] XdrError),
+    #[error("strval")]
+    StrVal(#[from] StrValError),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
+}
+
+enum Change {
+    Added(Value),
+    Removed(Value),
+    Modified { before: Value, after: Value },
+}
+
+/// Changes of a single contract's entries, or of the non-contract entries when the contract id
+/// is `None`.
+#[derive(Default)]
+struct Changes(Vec<(Value, Change)>);
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let before = read(&self.before)?;
+        let after = read(&self.after)?;
+        let changes = diff(&before, &after)?;
+
+        match self.output {
+            Output::Text => print_text(&changes)?,
+            Output::Json => println!("{}", serde_json::to_string_pretty(&to_json(&changes))?),
+        }
+        Ok(())
+    }
+}
+
+/// Compares the entries of two snapshots, grouping the changes by contract.
+fn diff(
+    before: &OrdMap<LedgerKey, LedgerEntry>,
+    after: &OrdMap<LedgerKey, LedgerEntry>,
+) -> Result<BTreeMap<Option<[u8; 32]>, Changes>, Error> {
+    let mut changes: BTreeMap<Option<[u8; 32]>, Changes> = BTreeMap::new();
+    for (key, entry) in before {
+        let (contract_id, k, v) = render_entry(key, entry)?;
+        let change = match after.get(key) {
+            None => Change::Removed(v),
+            Some(after_entry) if after_entry.data == entry.data => continue,
+            Some(after_entry) => Change::Modified {
+                before: v,
+                after: render_entry(key, after_entry)?.2,
+            },
+        };
+        changes.entry(contract_id).or_default().0.push((k, change));
+    }
+    for (key, entry) in after {
+        if before.contains_key(key) {
+            continue;
+        }
+        let (contract_id, k, v) = render_entry(key, entry)?;
+        changes
+            .entry(contract_id)
+            .or_default()
+            .0
+            .push((k, Change::Added(v)));
+    }
+    Ok(changes)
+}
+
+fn read(filepath: &PathBuf) -> Result<OrdMap<LedgerKey, LedgerEntry>, Error> {
+    // snapshot::read treats a missing file as an empty ledger, which would make a typo look like
+    // every entry was added or removed.
+    if !filepath.exists() {
+        return Err(Error::FileNotFound(filepath.clone()));
+    }
+    snapshot::read(filepath).map_err(|e| Error::CannotReadLedgerFile {
+        filepath: filepath.clone(),
+        error: e,
+    })
+}
+
+/// Returns the contract the entry belongs to, along with json renderings of its key and value.
+/// Contract code is rendered by its WASM hash, and entries that aren't contract data are
+/// rendered as base64-encoded xdr.
+fn render_entry(
+    key: &LedgerKey,
+    entry: &LedgerEntry,
+) -> Result<(Option<[u8; 32]>, Value, Value), Error> {
+    if let LedgerEntryData::ContractData(data) = &entry.data {
+        let contract_id = Some(data.contract_id.0);
+        if let ScVal::Static(ScStatic::LedgerKeyContractCode) = data.key {
+            let value = match &data.val {
+                ScVal::Object(Some(ScObject::ContractCode(ScContractCode::Wasm(wasm)))) => {
+                    json!({ "wasmHash": hex::encode(Sha256::digest(wasm.as_slice())) })
+                }
+                val => strval::to_json(val)?,
+            };
+            return Ok((contract_id, json!("contractCode"), value));
+        }
+        return Ok((
+            contract_id,
+            strval::to_json(&data.key)?,
+            strval::to_json(&data.val)?,
+        ));
+    }
+    Ok((
+        None,
+        Value::String(key.to_xdr_base64()?),
+        Value::String(entry.data.to_xdr_base64()?),
+    ))
+}
+
+fn print_text(changes: &BTreeMap<Option<[u8; 32]>, Changes>) -> Result<(), Error> {
+    for (contract_id, Changes(entries)) in changes {
+        match contract_id {
+            Some(id) => println!(
+                "Contract {} ({})",
+                hex::encode(id),
+                utils::contract_id_to_strkey(id)
+            ),
+            None => println!("Other entries"),
+        }
+        for (key, change) in entries {
+            let key = serde_json::to_string(key)?;
+            match change {
+                Change::Added(v) => println!(" + {}: {}", key, serde_json::to_string(v)?),
+                Change::Removed(v) => println!(" - {}: {}", key, serde_json::to_string(v)?),
+                Change::Modified { before, after } => println!(
+                    " ~ {}: {} -> {}",
+                    key,
+                    serde_json::to_string(before)?,
+                    serde_json::to_string(after)?,
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_json(changes: &BTreeMap<Option<[u8; 32]>, Changes>) -> Value {
+    let mut contracts = vec![];
+    let mut other = json!({ "added": [], "removed": [], "modified": [] });
+    for (contract_id, Changes(entries)) in changes {
+        let mut added = vec![];
+        let mut removed = vec![];
+        let mut modified = vec![];
+        for (key, change) in entries {
+            match change {
+                Change::Added(v) => added.push(json!({ "key": key, "value": v })),
+                Change::Removed(v) => removed.push(json!({ "key": key, "value": v })),
+                Change::Modified { before, after } => modified.push(json!({
+                    "key": key,
+                    "before": before,
+                    "after": after,
+                })),
+            }
+        }
+        let diff = json!({ "added": added, "removed": removed, "modified": modified });
+        match contract_id {
+            Some(id) => contracts.push(json!({
+                "id": hex::encode(id),
+                "strkey": utils::contract_id_to_strkey(id),
+                "changes": diff,
+            })),
+            None => other = diff,
+        }
+    }
+    json!({ "contracts": contracts, "other": other })
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_env_host::xdr::{ContractDataEntry, LedgerEntryExt, LedgerKeyContractData};
+
+    use super::*;
+
+    const ID: [u8; 32] = [1; 32];
+
+    fn set(entries: &mut OrdMap<LedgerKey, LedgerEntry>, key: u32, val: u32) {
+        entries.insert(
+            LedgerKey::ContractData(LedgerKeyContractData {
+                contract_id: ID.into(),
+                key: ScVal::U32(key),
+            }),
+            LedgerEntry {
+                last_modified_ledger_seq: 0,
+                data: LedgerEntryData::ContractData(ContractDataEntry {
+                    contract_id: ID.into(),
+                    key: ScVal::U32(key),
+                    val: ScVal::U32(val),
+                }),
+                ext: LedgerEntryExt::V0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut before = OrdMap::new();
+        utils::add_contract_to_ledger_entries(&mut before, ID, vec![0]).unwrap();
+        set(&mut before, 1, 1);
+        set(&mut before, 2, 2);
+        set(&mut before, 3, 3);
+        let mut after = before.clone();
+        set(&mut after, 1, 10);
+        after.remove(&LedgerKey::ContractData(LedgerKeyContractData {
+            contract_id: ID.into(),
+            key: ScVal::U32(2),
+        }));
+        set(&mut after, 4, 4);
+
+        let u32_json = |v| strval::to_json(&ScVal::U32(v)).unwrap();
+        // The unchanged code and entry 3 aren't listed
+        assert_eq!(
+            to_json(&diff(&before, &after).unwrap()),
+            json!({
+                "contracts": [{
+                    "id": hex::encode(ID),
+                    "strkey": utils::contract_id_to_strkey(&ID),
+                    "changes": {
+                        "added": [{ "key": u32_json(4), "value": u32_json(4) }],
+                        "removed": [{ "key": u32_json(2), "value": u32_json(2) }],
+                        "modified": [{ "key": u32_json(1), "before": u32_json(1), "after": u32_json(10) }],
+                    },
+                }],
+                "other": { "added": [], "removed": [], "modified": [] },
+            })
+        );
+        assert!(diff(&before, &before).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+use std::{fmt::Debug, fs, io, path::PathBuf};
+
+use clap::Parser;
+
+use crate::wasm;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Contract ID to export the WASM of
+    #[clap(long = "id")]
+    contract_id: String,
+    /// File to write the WASM to
+    #[clap(long, parse(from_os_str))]
+    out: PathBuf,
+    /// File to read ledger state from
+    #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
+    ledger_file: PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("writing file {filepath}: {error}")]
+    CannotWriteContractFile { filepath: PathBuf, error: io::Error },
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contents =
+            wasm::from_ledger(&self.ledger_file, &self.contract_id, self.allow_short_id)?;
+        fs::write(&self.out, contents).map_err(|e| Error::CannotWriteContractFile {
+            filepath: self.out.clone(),
+            error: e,
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Subcommand;
+
+pub mod diff;
+pub mod export_wasm;
+pub mod rm_contract;
+
+#[derive(Subcommand, Debug)]
+pub enum Cmd {
+    /// Compare the entries of two ledger snapshot files
+    Diff(diff::Cmd),
+    /// Write the WASM of a contract deployed in the ledger to a file
+    ExportWasm(export_wasm::Cmd),
+    /// Remove a contract's code and all of its data from the ledger
+    RmContract(rm_contract::Cmd),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Diff(#[from] diff::Error),
+    #[error(transparent)]
+    ExportWasm(#[from] export_wasm::Error),
+    #[error(transparent)]
+    RmContract(#[from] rm_contract::Error),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        match self {
+            Cmd::Diff(diff) => diff.run()?,
+            Cmd::ExportWasm(export_wasm) => export_wasm.run()?,
+            Cmd::RmContract(rm_contract) => rm_contract.run()?,
+        };
+        Ok(())
+    }
+}
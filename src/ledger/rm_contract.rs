@@ -0,0 +1,117 @@
+use std::{fmt::Debug, path::PathBuf};
+
+use clap::Parser;
+use soroban_env_host::{
+    im_rc::OrdMap,
+    xdr::{LedgerEntry, LedgerKey},
+};
+
+use crate::snapshot;
+use crate::utils;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Contract ID to remove
+    #[clap(long = "id")]
+    contract_id: String,
+    /// File to persist ledger state
+    #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
+    ledger_file: PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    ContractId(#[from] utils::ContractIdError),
+    #[error("snapshot")]
+    Snapshot(#[from] snapshot::Error),
+    #[error("contract {0} has no entries in the ledger")]
+    ContractNotFound(String),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contract_id = utils::contract_id_from_str(&self.contract_id, self.allow_short_id)?;
+        let mut ledger_entries = snapshot::read(&self.ledger_file)?;
+        let removed = remove_contract(&mut ledger_entries, contract_id);
+        if removed == 0 {
+            return Err(Error::ContractNotFound(utils::contract_id_to_strkey(
+                &contract_id,
+            )));
+        }
+
+        snapshot::commit(ledger_entries, [], &self.ledger_file)?;
+        println!(
+            "Removed {} entries of contract {} ({})",
+            removed,
+            hex::encode(contract_id),
+            utils::contract_id_to_strkey(&contract_id)
+        );
+        Ok(())
+    }
+}
+
+/// Removes the contract code along with all of the contract's data, returning the number of
+/// entries removed.
+fn remove_contract(
+    ledger_entries: &mut OrdMap<LedgerKey, LedgerEntry>,
+    contract_id: [u8; 32],
+) -> usize {
+    let keys: Vec<LedgerKey> = ledger_entries
+        .keys()
+        .filter(|k| matches!(k, LedgerKey::ContractData(k) if k.contract_id.0 == contract_id))
+        .cloned()
+        .collect();
+    for key in &keys {
+        ledger_entries.remove(key);
+    }
+    keys.len()
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_env_host::xdr::{
+        ContractDataEntry, LedgerEntryData, LedgerEntryExt, LedgerKeyContractData, ScVal,
+    };
+
+    use super::*;
+
+    fn add_data(entries: &mut OrdMap<LedgerKey, LedgerEntry>, contract_id: [u8; 32], key: u32) {
+        entries.insert(
+            LedgerKey::ContractData(LedgerKeyContractData {
+                contract_id: contract_id.into(),
+                key: ScVal::U32(key),
+            }),
+            LedgerEntry {
+                last_modified_ledger_seq: 0,
+                data: LedgerEntryData::ContractData(ContractDataEntry {
+                    contract_id: contract_id.into(),
+                    key: ScVal::U32(key),
+                    val: ScVal::U32(key),
+                }),
+                ext: LedgerEntryExt::V0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_remove_contract() {
+        let mut entries = OrdMap::new();
+        for id in [[1; 32], [2; 32]] {
+            utils::add_contract_to_ledger_entries(&mut entries, id, vec![0]).unwrap();
+            add_data(&mut entries, id, 1);
+            add_data(&mut entries, id, 2);
+        }
+
+        assert_eq!(remove_contract(&mut entries, [1; 32]), 3);
+        // Only the other contract's code and data are left
+        assert_eq!(entries.len(), 3);
+        assert!(entries
+            .keys()
+            .all(|k| matches!(k, LedgerKey::ContractData(k) if k.contract_id.0 == [2; 32])));
+        assert_eq!(remove_contract(&mut entries, [1; 32]), 0);
+    }
+}
@@ -0,0 +1,460 @@
+use std::{fmt::Debug, rc::Rc};
+
+use clap::{ArgEnum, Parser};
+use serde_json::json;
+use soroban_env_host::{budget::Budget, im_rc::OrdMap, meta, storage::Storage, xdr, Host, Vm};
+use wasmparser::{Operator, Payload, Type, TypeRef, ValType, Validator, WasmFeatures};
+
+use crate::snapshot;
+use crate::wasm;
+
+/// Lists the module names of the host interface passed by `call_macro_with_all_host_functions`.
+macro_rules! host_modules {
+    { $(mod $mod_id:ident $mod_str:literal { $($functions:tt)* })* } => {
+        &[$($mod_str),*]
+    };
+}
+
+/// Modules that host functions are imported from, as defined by the soroban-env interface this
+/// CLI is built with.
+const HOST_MODULES: &[&str] =
+    soroban_env_host::call_macro_with_all_host_functions! { host_modules };
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    #[clap(flatten)]
+    wasm: wasm::Args,
+    /// Largest data segment, in bytes, that is not reported
+    #[clap(long, default_value = "16384")]
+    max_data_segment_size: usize,
+    /// Largest number of exported functions that is not reported
+    #[clap(long, default_value = "64")]
+    max_exports: usize,
+    /// Type of output to generate
+    #[clap(long, arg_enum, default_value = "text")]
+    output: Output,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Output {
+    /// Human readable listing of the findings
+    Text,
+    /// Json document of the findings
+    Json,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("parsing wasm: {0}")]
+    WasmParse(#[from] wasmparser::BinaryReaderError),
+    #[error("xdr")]
+    Xdr(#[from] xdr::Error),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
+    #[error("{0} error(s) found")]
+    Failed(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+pub struct Finding {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contents = self.wasm.read()?;
+        let findings = self.lint(&contents)?;
+
+        match self.output {
+            Output::Text => {
+                for f in &findings {
+                    println!("{}[{}]: {}", f.severity.as_str(), f.code, f.message);
+                }
+            }
+            Output::Json => {
+                let findings = findings
+                    .iter()
+                    .map(|f| {
+                        json!({
+                            "code": f.code,
+                            "severity": f.severity.as_str(),
+                            "message": f.message,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&findings)?);
+            }
+        }
+
+        let errors = findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count();
+        if errors > 0 {
+            return Err(Error::Failed(errors));
+        }
+        Ok(())
+    }
+
+    fn lint(&self, contents: &[u8]) -> Result<Vec<Finding>, Error> {
+        let mut findings = vec![];
+        let mut report = |code, severity, message| {
+            findings.push(Finding {
+                code,
+                severity,
+                message,
+            });
+        };
+
+        // S001: The VM only supports the MVP with a couple of small extensions, so anything
+        // relying on other proposals will fail to load on-chain.
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            mutable_global: true,
+            sign_extension: true,
+            saturating_float_to_int: false,
+            bulk_memory: false,
+            multi_value: false,
+            reference_types: false,
+            simd: false,
+            ..WasmFeatures::default()
+        });
+        if let Err(e) = validator.validate_all(contents) {
+            report("S001", Severity::Error, format!("unsupported wasm: {}", e));
+        }
+
+        let module = wasm::parse(contents)?;
+        let function_imports = module.function_imports_count();
+
+        // S002: Floating-point arithmetic is non-deterministic across platforms, so floats
+        // aren't allowed in signatures, globals and imports either.
+        let mut float_types = vec![];
+        let mut global_index = module
+            .imports
+            .iter()
+            .filter(|i| matches!(i.ty, TypeRef::Global(_)))
+            .count();
+        let mut index = function_imports;
+        for payload in wasmparser::Parser::new(0).parse_all(contents) {
+            match payload? {
+                Payload::TypeSection(types) => {
+                    for (i, ty) in types.into_iter().enumerate() {
+                        let Type::Func(f) = ty?;
+                        if f.params()
+                            .iter()
+                            .chain(f.results())
+                            .any(|t| is_float_type(*t))
+                        {
+                            float_types.push(i);
+                            report(
+                                "S002",
+                                Severity::Error,
+                                format!("type {} has floating-point parameters or results", i),
+                            );
+                        }
+                    }
+                }
+                Payload::ImportSection(imports) => {
+                    for import in imports {
+                        let import = import?;
+                        let uses_float = match import.ty {
+                            TypeRef::Func(ty) => float_types.contains(&(ty as usize)),
+                            TypeRef::Global(g) => is_float_type(g.content_type),
+                            _ => false,
+                        };
+                        if uses_float {
+                            report(
+                                "S002",
+                                Severity::Error,
+                                format!(
+                                    "import {}.{} has a floating-point type",
+                                    import.module, import.name
+                                ),
+                            );
+                        }
+                    }
+                }
+                Payload::GlobalSection(globals) => {
+                    for global in globals {
+                        if is_float_type(global?.ty.content_type) {
+                            report(
+                                "S002",
+                                Severity::Error,
+                                format!("global {} is floating-point", global_index),
+                            );
+                        }
+                        global_index += 1;
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut uses_float = false;
+                    let mut locals = body.get_locals_reader()?;
+                    for _ in 0..locals.get_count() {
+                        let (_, ty) = locals.read()?;
+                        uses_float |= is_float_type(ty);
+                    }
+                    for op in body.get_operators_reader()? {
+                        if uses_float {
+                            break;
+                        }
+                        uses_float = is_float_operator(&op?);
+                    }
+                    if uses_float {
+                        report(
+                            "S002",
+                            Severity::Error,
+                            format!("function {} uses floating-point values", index),
+                        );
+                    }
+                    index += 1;
+                }
+                _ => {}
+            }
+        }
+
+        // S003: Data segments are part of the contract code, which is paid for on every load.
+        for (i, size) in module.data_segment_sizes.iter().enumerate() {
+            if *size > self.max_data_segment_size {
+                report(
+                    "S003",
+                    Severity::Warning,
+                    format!("data segment {} is {} bytes", i, size),
+                );
+            }
+        }
+
+        // S004, S005: Without a spec the contract can't be invoked by name, and without env meta
+        // the host can't check the interface version.
+        if module.spec.is_none() {
+            report(
+                "S004",
+                Severity::Error,
+                "missing contractspecv0 section".to_string(),
+            );
+        }
+        match module.interface_version()? {
+            Some(v) if v != meta::INTERFACE_VERSION => report(
+                "S005",
+                Severity::Error,
+                format!(
+                    "interface version {} does not match the host interface version {}",
+                    v,
+                    meta::INTERFACE_VERSION
+                ),
+            ),
+            Some(_) => {}
+            None => report(
+                "S005",
+                Severity::Error,
+                "missing interface version in contractenvmetav0 section".to_string(),
+            ),
+        }
+
+        // S006: A start function runs on every instantiation, before the invoked function.
+        if let Some(func) = module.start_function {
+            report(
+                "S006",
+                Severity::Warning,
+                format!("module has a start function ({})", func),
+            );
+        }
+
+        // S007: Every export adds to the size of the contract and its attack surface.
+        let exports = module.function_exports().len();
+        if exports > self.max_exports {
+            report(
+                "S007",
+                Severity::Warning,
+                format!("module exports {} functions", exports),
+            );
+        }
+
+        // S008: Imports from anywhere other than the host can never be resolved.
+        for import in &module.imports {
+            if !HOST_MODULES.contains(&import.module) {
+                report(
+                    "S008",
+                    Severity::Error,
+                    format!(
+                        "import {}.{} is not from a known host module",
+                        import.module, import.name
+                    ),
+                );
+            }
+        }
+        // S009: Imports of functions the host doesn't provide, and anything else the VM rejects,
+        // fail at instantiation.
+        let snap = Rc::new(snapshot::Snap {
+            ledger_entries: OrdMap::new(),
+        });
+        let h = Host::with_storage_and_budget(
+            Storage::with_recording_footprint(snap),
+            Budget::default(),
+        );
+        if let Err(e) = Vm::new(&h, [0; 32].into(), contents) {
+            report(
+                "S009",
+                Severity::Error,
+                format!("module does not instantiate with the host: {:?}", e),
+            );
+        }
+
+        Ok(findings)
+    }
+}
+
+fn is_float_type(ty: ValType) -> bool {
+    matches!(ty, ValType::F32 | ValType::F64)
+}
+
+/// Whether the operator operates on, produces or converts floating-point values. SIMD operators
+/// are not listed, as the validator already rejects them.
+fn is_float_operator(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        F32Load { .. }
+            | F64Load { .. }
+            | F32Store { .. }
+            | F64Store { .. }
+            | F32Const { .. }
+            | F64Const { .. }
+            | F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | F32Abs
+            | F32Neg
+            | F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32TruncF32S
+            | I32TruncF32U
+            | I32TruncF64S
+            | I32TruncF64U
+            | I64TruncF32S
+            | I64TruncF32U
+            | I64TruncF64S
+            | I64TruncF64U
+            | F32ConvertI32S
+            | F32ConvertI32U
+            | F32ConvertI64S
+            | F32ConvertI64U
+            | F32DemoteF64
+            | F64ConvertI32S
+            | F64ConvertI32U
+            | F64ConvertI64S
+            | F64ConvertI64U
+            | F64PromoteF32
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+            | I32TruncSatF32S
+            | I32TruncSatF32U
+            | I32TruncSatF64S
+            | I32TruncSatF64U
+            | I64TruncSatF32S
+            | I64TruncSatF32U
+            | I64TruncSatF64S
+            | I64TruncSatF64U
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_host_modules() {
+        assert!(!HOST_MODULES.is_empty());
+        assert!(HOST_MODULES.contains(&"x"));
+    }
+
+    #[test]
+    fn test_is_float_operator() {
+        assert!(is_float_operator(&Operator::F64Add));
+        assert!(is_float_operator(&Operator::I32TruncF32S));
+        assert!(is_float_operator(&Operator::I64ReinterpretF64));
+        assert!(!is_float_operator(&Operator::I64Add));
+        assert!(!is_float_operator(&Operator::Nop));
+        assert!(is_float_type(ValType::F32));
+        assert!(!is_float_type(ValType::I64));
+    }
+
+    #[test]
+    fn test_float_signatures_globals_and_imports() {
+        // A type (f64) -> (), an imported f64 global and an f32 global
+        let types = [1, 0x60, 1, 0x7c, 0];
+        let imports = [1, 1, b'x', 1, b'g', 3, 0x7c, 0];
+        let globals = [1, 0x7d, 0, 0x43, 0, 0, 0, 0, 0x0b];
+        let contents = wasm::encode_sections(
+            b"\0asm\x01\0\0\0",
+            [(1, &types[..]), (2, &imports[..]), (6, &globals[..])],
+        );
+
+        let cmd = Cmd::parse_from(["lint", "--wasm", "test.wasm"]);
+        let findings = cmd.lint(&contents).unwrap();
+        let floats = findings
+            .iter()
+            .filter(|f| f.code == "S002")
+            .map(|f| f.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            floats,
+            [
+                "type 0 has floating-point parameters or results",
+                "import x.g has a floating-point type",
+                "global 1 is floating-point",
+            ]
+        );
+    }
+}
@@ -7,13 +7,21 @@ mod gen;
 mod inspect;
 mod invoke;
 mod jsonrpc;
+mod ledger;
+mod lint;
 mod network;
+mod optimize;
 mod read;
 mod serve;
 mod snapshot;
+mod spec;
+mod specdiff;
 mod strval;
+#[cfg(test)]
+mod testutils;
 mod utils;
 mod version;
+mod wasm;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -42,6 +50,19 @@ enum Cmd {
     Deploy(deploy::Cmd),
     /// Generate code client bindings for a contract
     Gen(gen::Cmd),
+    /// Inspect and manage ledger state files
+    #[clap(subcommand)]
+    Ledger(ledger::Cmd),
+    /// Extract or replace the contract spec of a WASM file
+    #[clap(subcommand)]
+    Spec(spec::Cmd),
+    /// Compare the contract specs of two WASM files, failing on breaking changes
+    SpecDiff(specdiff::Cmd),
+    /// Check a WASM file for constructs that fail or are costly on-chain
+    Lint(lint::Cmd),
+    /// Strip non-essential custom sections, and optionally unused exports, from a WASM file
+    #[clap(alias = "strip")]
+    Optimize(optimize::Cmd),
 
     /// Print version information
     Version(version::Cmd),
@@ -65,6 +86,16 @@ enum CmdError {
     Gen(#[from] gen::Error),
     #[error("{0:?}")]
     Deploy(#[from] deploy::Error),
+    #[error(transparent)]
+    Ledger(#[from] ledger::Error),
+    #[error(transparent)]
+    Spec(#[from] spec::Error),
+    #[error(transparent)]
+    SpecDiff(#[from] specdiff::Error),
+    #[error(transparent)]
+    Lint(#[from] lint::Error),
+    #[error(transparent)]
+    Optimize(#[from] optimize::Error),
 }
 
 async fn run(cmd: Cmd, matches: &mut clap::ArgMatches) -> Result<(), CmdError> {
@@ -78,6 +109,11 @@ async fn run(cmd: Cmd, matches: &mut clap::ArgMatches) -> Result<(), CmdError> {
         Cmd::Serve(serve) => serve.run().await?,
         Cmd::Gen(gen) => gen.run()?,
         Cmd::Deploy(deploy) => deploy.run()?,
+        Cmd::Ledger(ledger) => ledger.run()?,
+        Cmd::Spec(spec) => spec.run()?,
+        Cmd::SpecDiff(spec_diff) => spec_diff.run()?,
+        Cmd::Lint(lint) => lint.run()?,
+        Cmd::Optimize(optimize) => optimize.run()?,
         Cmd::Version(version) => version.run(),
         Cmd::Completion(completion) => completion.run(&mut Root::command()),
     };
@@ -100,5 +136,9 @@ async fn main() {
 
     if let Err(e) = run(root.cmd, &mut saved_matches).await {
         eprintln!("error: {}", e);
+        // spec-diff fails CI on breaking changes
+        if matches!(e, CmdError::SpecDiff(_)) {
+            std::process::exit(1);
+        }
     }
 }
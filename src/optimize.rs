@@ -0,0 +1,197 @@
+use std::{fmt::Debug, fs, io, path::PathBuf, rc::Rc};
+
+use clap::Parser;
+use soroban_env_host::{
+    budget::Budget, im_rc::OrdMap, storage::Storage, xdr, xdr::ScSpecEntry, Host, HostError, Vm,
+};
+
+use crate::snapshot;
+use crate::wasm;
+
+/// Custom sections that the host and the CLI need, which are always kept.
+const REQUIRED_SECTIONS: &[&str] = &["contractspecv0", "contractenvmetav0"];
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    #[clap(flatten)]
+    wasm: wasm::Args,
+    /// File to write the optimized WASM to
+    #[clap(long, parse(from_os_str))]
+    out: PathBuf,
+    /// Custom section to keep, in addition to contractspecv0 and contractenvmetav0
+    #[clap(long = "keep-section", value_name = "name", multiple = true)]
+    keep_sections: Vec<String>,
+    /// Remove the exports of functions that aren't declared in the contract spec, so they can't
+    /// be invoked. Their code is kept, so this barely reduces the size on its own, but it lets a
+    /// tool like wasm-opt remove the code afterwards.
+    #[clap(long)]
+    drop_unused_exports: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("parsing wasm: {0}")]
+    WasmParse(#[from] wasmparser::BinaryReaderError),
+    #[error("malformed wasm module")]
+    Malformed,
+    #[error("xdr")]
+    Xdr(#[from] xdr::Error),
+    #[error("optimized contract cannot be instantiated: {0}")]
+    Instantiate(HostError),
+    #[error("optimized contract spec differs from the original")]
+    SpecChanged,
+    #[error("writing file {filepath}: {error}")]
+    CannotWriteContractFile { filepath: PathBuf, error: io::Error },
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contents = self.wasm.read()?;
+        let module = wasm::parse(&contents)?;
+        let spec = module.spec_entries()?;
+
+        let exports = self.kept_exports(&module, &spec);
+        let exports_section = wasm::RawSection::exports(&exports);
+        let sections = wasm::raw_sections(&contents).ok_or(Error::Malformed)?;
+        let kept = sections.iter().filter_map(|s| match s.custom_name() {
+            Some(name) if self.is_kept(name) => Some((s.id, s.contents)),
+            Some(_) => None,
+            None if s.is_export() => Some((s.id, exports_section.as_slice())),
+            None => Some((s.id, s.contents)),
+        });
+        let optimized = wasm::encode_sections(&contents, kept);
+
+        // Check the optimized module still works the same for the host and for callers
+        let optimized_module = wasm::parse(&optimized)?;
+        if optimized_module.spec_entries()? != spec {
+            return Err(Error::SpecChanged);
+        }
+        let snap = Rc::new(snapshot::Snap {
+            ledger_entries: OrdMap::new(),
+        });
+        let h = Host::with_storage_and_budget(
+            Storage::with_recording_footprint(snap),
+            Budget::default(),
+        );
+        Vm::new(&h, [0; 32].into(), &optimized).map_err(Error::Instantiate)?;
+
+        fs::write(&self.out, &optimized).map_err(|e| Error::CannotWriteContractFile {
+            filepath: self.out.clone(),
+            error: e,
+        })?;
+        println!(
+            "Size: {} bytes -> {} bytes",
+            contents.len(),
+            optimized.len()
+        );
+        Ok(())
+    }
+
+    fn is_kept(&self, section: &str) -> bool {
+        REQUIRED_SECTIONS.contains(&section) || self.keep_sections.iter().any(|s| s == section)
+    }
+
+    fn kept_exports<'a>(
+        &self,
+        module: &wasm::Module<'a>,
+        spec: &[ScSpecEntry],
+    ) -> Vec<wasmparser::Export<'a>> {
+        module
+            .exports
+            .iter()
+            .filter(|e| {
+                !self.drop_unused_exports
+                    || e.kind != wasmparser::ExternalKind::Func
+                    || spec.iter().any(|entry| match entry {
+                        ScSpecEntry::FunctionV0(f) => f.name.as_slice() == e.name.as_bytes(),
+                        _ => false,
+                    })
+            })
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils;
+
+    /// Optimizes the test contract with the extra arguments, and checks the result loads in the
+    /// host and has the same spec.
+    fn optimize(name: &str, args: &[&str]) -> Vec<u8> {
+        let wasm = std::env::temp_dir().join(format!(
+            "soroban-cli-optimize-{}-{}.wasm",
+            std::process::id(),
+            name
+        ));
+        let out = wasm.with_extension("out.wasm");
+        fs::write(&wasm, testutils::contract()).unwrap();
+
+        let mut argv = vec![
+            "optimize",
+            "--wasm",
+            wasm.to_str().unwrap(),
+            "--out",
+            out.to_str().unwrap(),
+        ];
+        argv.extend_from_slice(args);
+        Cmd::parse_from(argv).run().unwrap();
+
+        let optimized = fs::read(&out).unwrap();
+        fs::remove_file(wasm).unwrap();
+        fs::remove_file(out).unwrap();
+        Vm::new(&Host::default(), [0; 32].into(), &optimized).unwrap();
+        assert_eq!(
+            wasm::parse(&optimized).unwrap().spec_entries().unwrap(),
+            testutils::spec()
+        );
+        optimized
+    }
+
+    fn custom_sections(contents: &[u8]) -> Vec<&str> {
+        wasm::parse(contents)
+            .unwrap()
+            .custom_sections
+            .iter()
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    fn exports(contents: &[u8]) -> Vec<&str> {
+        wasm::parse(contents)
+            .unwrap()
+            .exports
+            .iter()
+            .map(|e| e.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_keeps_only_required_sections() {
+        let optimized = optimize("default", &[]);
+        assert_eq!(
+            custom_sections(&optimized),
+            ["contractenvmetav0", "contractspecv0"]
+        );
+        assert_eq!(exports(&optimized), ["hello", "unused", "memory"]);
+    }
+
+    #[test]
+    fn test_keep_section() {
+        let optimized = optimize("keep-section", &["--keep-section", "extra"]);
+        assert_eq!(
+            custom_sections(&optimized),
+            ["contractenvmetav0", "contractspecv0", "extra"]
+        );
+    }
+
+    #[test]
+    fn test_drop_unused_exports() {
+        let optimized = optimize("drop-unused-exports", &["--drop-unused-exports"]);
+        // Exports other than functions are kept, as are functions in the spec
+        assert_eq!(exports(&optimized), ["hello", "memory"]);
+    }
+}
@@ -1,17 +1,16 @@
 use std::{fmt::Debug, io, rc::Rc};
 
-use clap::Parser;
+use clap::{ArgEnum, Parser};
+use serde_json::Value;
 use soroban_env_host::{
     storage::Storage,
     xdr::{
-        self, Error as XdrError, LedgerEntryData, LedgerKey, LedgerKeyContractData, ReadXdr, ScVal,
-        WriteXdr,
+        self, Error as XdrError, LedgerEntryData, LedgerKey, LedgerKeyContractData, ReadXdr,
+        ScSpecTypeDef, ScVal, WriteXdr,
     },
     HostError,
 };
 
-use hex::FromHexError;
-
 use crate::snapshot;
 use crate::strval::{self, StrValError};
 use crate::utils;
@@ -22,14 +21,54 @@ pub struct Cmd {
     #[clap(long = "id")]
     contract_id: String,
     /// Storage key to read from, base64-encoded xdr
-    #[clap(long = "key")]
-    key: String,
+    #[clap(
+        long = "key",
+        conflicts_with = "key-json",
+        required_unless_present = "key-json"
+    )]
+    key: Option<String>,
+    /// Storage key to read from, as json (plain strings are treated as symbols)
+    #[clap(long = "key-json")]
+    key_json: Option<String>,
+    /// Type of the json storage key [default: symbol]
+    #[clap(long = "key-type", arg_enum, requires = "key-json")]
+    key_type: Option<KeyType>,
     /// Output the result as json, instead of base64-encoded xdr
     #[clap(long = "json")]
     json: bool,
     /// File to persist ledger state
     #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
     ledger_file: std::path::PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum KeyType {
+    Symbol,
+    U32,
+    I32,
+    U64,
+    I64,
+    Bool,
+    Bytes,
+    BigInt,
+}
+
+impl KeyType {
+    fn spec_type(self) -> ScSpecTypeDef {
+        match self {
+            KeyType::Symbol => ScSpecTypeDef::Symbol,
+            KeyType::U32 => ScSpecTypeDef::U32,
+            KeyType::I32 => ScSpecTypeDef::I32,
+            KeyType::U64 => ScSpecTypeDef::U64,
+            KeyType::I64 => ScSpecTypeDef::I64,
+            KeyType::Bool => ScSpecTypeDef::Bool,
+            KeyType::Bytes => ScSpecTypeDef::Bytes,
+            KeyType::BigInt => ScSpecTypeDef::BigInt,
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -46,14 +85,31 @@ pub enum Error {
     Snapshot(#[from] snapshot::Error),
     #[error("serde")]
     Serde(#[from] serde_json::Error),
-    #[error("hex")]
-    FromHex(#[from] FromHexError),
+    #[error(transparent)]
+    ContractId(#[from] utils::ContractIdError),
 }
 
 impl Cmd {
+    fn parse_key(&self) -> Result<ScVal, Error> {
+        if let Some(key) = &self.key {
+            return Ok(ScVal::from_xdr_base64(key.clone())?);
+        }
+        let key_json = self.key_json.as_deref().unwrap_or_default();
+        let t = self.key_type.unwrap_or(KeyType::Symbol).spec_type();
+        // Symbols passed as json strings (e.g. '"COUNTER"') need their quotes stripped, so they
+        // match how contract authors write `symbol!("COUNTER")`.
+        if let (ScSpecTypeDef::Symbol, Ok(Value::String(raw))) =
+            (&t, serde_json::from_str(key_json))
+        {
+            return Ok(strval::from_string(&raw, &t, &[])?);
+        }
+        Ok(strval::from_string(key_json, &t, &[])?)
+    }
+
     pub fn run(&self) -> Result<(), Error> {
-        let contract_id: [u8; 32] = utils::contract_id_from_str(&self.contract_id)?;
-        let key = ScVal::from_xdr_base64(self.key.clone())?;
+        let contract_id: [u8; 32] =
+            utils::contract_id_from_str(&self.contract_id, self.allow_short_id)?;
+        let key = self.parse_key()?;
 
         // Initialize storage
         let ledger_entries = snapshot::read(&self.ledger_file)?;
@@ -80,3 +136,34 @@ impl Cmd {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_without_key_json() {
+        let key = ScVal::U32(1).to_xdr_base64().unwrap();
+        let cmd = Cmd::try_parse_from(["read", "--id", "1", "--key", key.as_str()]).unwrap();
+        assert_eq!(cmd.parse_key().unwrap(), ScVal::U32(1));
+        assert!(Cmd::try_parse_from(["read", "--id", "1", "--key", "foo"]).is_ok());
+        // The key type only applies to --key-json
+        assert!(
+            Cmd::try_parse_from(["read", "--id", "1", "--key", "foo", "--key-type", "u32"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_key_json_defaults_to_symbol() {
+        let cmd = Cmd::try_parse_from(["read", "--id", "1", "--key-json", "\"COUNTER\""]).unwrap();
+        assert_eq!(
+            cmd.parse_key().unwrap(),
+            ScVal::Symbol("COUNTER".as_bytes().try_into().unwrap())
+        );
+        let cmd =
+            Cmd::try_parse_from(["read", "--id", "1", "--key-json", "7", "--key-type", "u32"])
+                .unwrap();
+        assert_eq!(cmd.parse_key().unwrap(), ScVal::U32(7));
+    }
+}
@@ -2,16 +2,16 @@ use std::collections::HashMap;
 use std::{convert::Infallible, fmt::Debug, io, net::SocketAddr, path::PathBuf, rc::Rc, sync::Arc};
 
 use clap::Parser;
-use hex::FromHexError;
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use soroban_env_host::{
     budget::Budget,
     storage::{AccessType, Footprint, Storage},
     xdr::{
-        self, Error as XdrError, FeeBumpTransactionInnerTx, HostFunction, LedgerEntryData,
-        LedgerKey, LedgerKeyContractData, OperationBody, ReadXdr, ScHostStorageErrorCode, ScObject,
-        ScStatus, ScVal, TransactionEnvelope, WriteXdr,
+        self, AccountId, Error as XdrError, FeeBumpTransactionInnerTx, HostFunction,
+        InvokeHostFunctionOp, LedgerEntryData, LedgerKey, LedgerKeyContractData, MuxedAccount,
+        OperationBody, PublicKey, ReadXdr, ScHostStorageErrorCode, ScObject, ScStatus, ScVal,
+        ScVec, TransactionEnvelope, Uint256, WriteXdr,
     },
     Host, HostError,
 };
@@ -32,6 +32,9 @@ pub struct Cmd {
     /// File to persist ledger state
     #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
     ledger_file: PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    allow_short_id: bool,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -48,8 +51,10 @@ pub enum Error {
     Snapshot(#[from] snapshot::Error),
     #[error("serde")]
     Serde(#[from] serde_json::Error),
-    #[error("hex")]
-    FromHex(#[from] FromHexError),
+    #[error(transparent)]
+    ContractId(#[from] utils::ContractIdError),
+    #[error("operation {index} failed: {error:?}")]
+    Operation { index: usize, error: HostError },
     #[error("unknownmethod")]
     UnknownMethod,
 }
@@ -66,6 +71,8 @@ impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
         let ledger_file = Arc::new(self.ledger_file.clone());
         let with_ledger_file = warp::any().map(move || ledger_file.clone());
+        let allow_short_id = self.allow_short_id;
+        let with_allow_short_id = warp::any().map(move || allow_short_id);
 
         // Just track in-flight transactions in-memory for sandbox for now. Simple.
         let transaction_status_map: Arc<Mutex<HashMap<String, Value>>> =
@@ -77,6 +84,7 @@ impl Cmd {
             .and(warp::body::json())
             .and(with_ledger_file)
             .and(with_transaction_status_map)
+            .and(with_allow_short_id)
             .and_then(handler);
 
         // Allow access from all remote sites when we are in local sandbox mode. (Always for now)
@@ -109,6 +117,7 @@ async fn handler(
     request: jsonrpc::Request<Requests>,
     ledger_file: Arc<PathBuf>,
     transaction_status_map: Arc<Mutex<HashMap<String, Value>>>,
+    allow_short_id: bool,
 ) -> Result<impl warp::Reply, Infallible> {
     let resp = Response::builder()
         .status(200)
@@ -128,7 +137,7 @@ async fn handler(
     }
     let result = match (request.method.as_str(), request.params) {
         ("getContractData", Some(Requests::GetContractData((contract_id, key)))) => {
-            get_contract_data(&contract_id, key, &ledger_file)
+            get_contract_data(&contract_id, key, &ledger_file, allow_short_id)
         }
         ("getTransactionStatus", Some(Requests::StringArg(b))) => {
             if let Some(hash) = b.into_vec().first() {
@@ -151,7 +160,7 @@ async fn handler(
             if let Some(txn_xdr) = b.into_vec().first() {
                 parse_transaction(txn_xdr, SANDBOX_NETWORK_PASSPHRASE)
                     // Execute and do NOT commit
-                    .and_then(|(_, args)| execute_transaction(&args, &ledger_file, false))
+                    .and_then(|txn| execute_transaction(&txn.operations, &ledger_file, false))
             } else {
                 Err(Error::Xdr(XdrError::Invalid))
             }
@@ -160,35 +169,34 @@ async fn handler(
             if let Some(txn_xdr) = b.into_vec().first() {
                 // TODO: Format error object output if txn is invalid
                 let mut m = transaction_status_map.lock().await;
-                parse_transaction(txn_xdr, SANDBOX_NETWORK_PASSPHRASE).map(|(hash, args)| {
-                    let id = hex::encode(hash);
+                parse_transaction(txn_xdr, SANDBOX_NETWORK_PASSPHRASE).map(|txn| {
+                    let id = hex::encode(txn.hash);
                     // Execute and commit
-                    let result = execute_transaction(&args, &ledger_file, true);
+                    let result = execute_transaction(&txn.operations, &ledger_file, true);
+                    let mut status = match result {
+                        Ok(result) => {
+                            json!({
+                                "id": id,
+                                "status": "success",
+                                "results": vec![result],
+                            })
+                        }
+                        Err(err) => {
+                            json!({
+                                "id": id,
+                                "status": "error",
+                                "error": {
+                                    "code":-32603,
+                                    "message": err.to_string(),
+                                },
+                            })
+                        }
+                    };
+                    if let Some(inner_hash) = txn.inner_hash {
+                        status["innerId"] = json!(hex::encode(inner_hash));
+                    }
                     // Add it to our status tracker
-                    m.insert(
-                        id.clone(),
-                        match result {
-                            Ok(result) => {
-                                json!({
-                                    "id": id,
-                                    "status": "success",
-                                    "results": vec![result],
-                                })
-                            }
-                            Err(_err) => {
-                                // TODO: Actually render the real error to the user
-                                // Add it to our status tracker
-                                json!({
-                                    "id": id,
-                                    "status": "error",
-                                    "error": {
-                                        "code":-32603,
-                                        "message": "Internal server error",
-                                    },
-                                })
-                            }
-                        },
-                    );
+                    m.insert(id.clone(), status);
                     // Return the hash
                     json!({ "id": id, "status": "pending" })
                 })
@@ -242,13 +250,14 @@ fn reply(
 }
 
 fn get_contract_data(
-    contract_id_hex: &str,
+    contract_id_str: &str,
     key_xdr: String,
     ledger_file: &PathBuf,
+    allow_short_id: bool,
 ) -> Result<Value, Error> {
     // Initialize storage and host
     let ledger_entries = snapshot::read(ledger_file)?;
-    let contract_id: [u8; 32] = utils::contract_id_from_str(&contract_id_hex.to_string())?;
+    let contract_id: [u8; 32] = utils::contract_id_from_str(contract_id_str, allow_short_id)?;
     let key = ScVal::from_xdr_base64(key_xdr)?;
 
     let snap = Rc::new(snapshot::Snap { ledger_entries });
@@ -265,6 +274,8 @@ fn get_contract_data(
     };
 
     Ok(json!({
+        "contractId": hex::encode(contract_id),
+        "contractIdStrkey": utils::contract_id_to_strkey(&contract_id),
         "xdr": value.to_xdr_base64()?,
         "lastModifiedLedgerSeq": ledger_entry.last_modified_ledger_seq,
         // TODO: Find "real" ledger seq number here
@@ -272,32 +283,106 @@ fn get_contract_data(
     }))
 }
 
-fn parse_transaction(txn_xdr: &str, passphrase: &str) -> Result<([u8; 32], Vec<ScVal>), Error> {
+/// A transaction parsed from its envelope, ready to execute.
+struct Transaction {
+    /// Hash of the envelope, which is the hash of the fee-bump transaction for fee-bumps
+    hash: [u8; 32],
+    /// Hash of the transaction wrapped by a fee-bump
+    inner_hash: Option<[u8; 32]>,
+    /// The host function of each operation, in order
+    operations: Vec<Operation>,
+}
+
+/// A host function invocation, run by the host the way the network runs it.
+struct Operation {
+    function: HostFunction,
+    parameters: ScVec,
+    /// The operation's source account, or the transaction's if it has none. Contracts created
+    /// with the source account get their ID from it.
+    source_account: [u8; 32],
+}
+
+fn parse_transaction(txn_xdr: &str, passphrase: &str) -> Result<Transaction, Error> {
     // Parse and validate the txn
     let transaction = TransactionEnvelope::from_xdr_base64(txn_xdr.to_string())?;
     let hash = hash_transaction_in_envelope(&transaction, passphrase)?;
-    let ops = match transaction {
-        TransactionEnvelope::TxV0(envelope) => envelope.tx.operations,
-        TransactionEnvelope::Tx(envelope) => envelope.tx.operations,
+    let (ops, source_account, inner_hash) = match transaction {
+        TransactionEnvelope::TxV0(envelope) => (
+            envelope.tx.operations,
+            envelope.tx.source_account_ed25519.0,
+            None,
+        ),
+        TransactionEnvelope::Tx(envelope) => (
+            envelope.tx.operations,
+            muxed_account_ed25519(&envelope.tx.source_account),
+            None,
+        ),
         TransactionEnvelope::TxFeeBump(envelope) => {
+            // The operations are the inner transaction's, which is hashed on its own
             let FeeBumpTransactionInnerTx::Tx(tx_envelope) = envelope.tx.inner_tx;
-            tx_envelope.tx.operations
+            let inner_hash = hash_transaction_in_envelope(
+                &TransactionEnvelope::Tx(tx_envelope.clone()),
+                passphrase,
+            )?;
+            (
+                tx_envelope.tx.operations,
+                muxed_account_ed25519(&tx_envelope.tx.source_account),
+                Some(inner_hash),
+            )
         }
     };
-    if ops.len() != 1 {
+    if ops.is_empty() {
         return Err(Error::Xdr(XdrError::Invalid));
     }
-    let op = ops.first().ok_or(Error::Xdr(XdrError::Invalid))?;
-    let body = if let OperationBody::InvokeHostFunction(b) = &op.body {
-        b
-    } else {
-        return Err(Error::Xdr(XdrError::Invalid));
-    };
+    let operations = ops
+        .iter()
+        .map(|op| {
+            if let OperationBody::InvokeHostFunction(b) = &op.body {
+                let source_account = op
+                    .source_account
+                    .as_ref()
+                    .map_or(source_account, muxed_account_ed25519);
+                parse_host_function(b, source_account)
+            } else {
+                Err(Error::Xdr(XdrError::Invalid))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    if body.function != HostFunction::Call {
-        return Err(Error::Xdr(XdrError::Invalid));
+    Ok(Transaction {
+        hash,
+        inner_hash,
+        operations,
+    })
+}
+
+fn muxed_account_ed25519(account: &MuxedAccount) -> [u8; 32] {
+    match account {
+        MuxedAccount::Ed25519(Uint256(key)) => *key,
+        MuxedAccount::MuxedEd25519(m) => m.ed25519.0,
+    }
+}
+
+fn parse_host_function(
+    body: &InvokeHostFunctionOp,
+    source_account: [u8; 32],
+) -> Result<Operation, Error> {
+    let parameters = match body.function {
+        HostFunction::Call => parse_call(body)?.try_into()?,
+        // The host checks the parameters, derives the contract ID, and for contracts created
+        // with an ed25519 key, verifies the key's signature
+        HostFunction::CreateContractWithSourceAccount | HostFunction::CreateContractWithEd25519 => {
+            body.parameters.clone()
+        }
     };
+    Ok(Operation {
+        function: body.function.clone(),
+        parameters,
+        source_account,
+    })
+}
 
+fn parse_call(body: &InvokeHostFunctionOp) -> Result<Vec<ScVal>, Error> {
     if body.parameters.len() < 2 {
         return Err(Error::Xdr(XdrError::Invalid));
     };
@@ -341,11 +426,13 @@ fn parse_transaction(txn_xdr: &str, passphrase: &str) -> Result<([u8; 32], Vec<S
     ];
     complete_args.extend_from_slice(params);
 
-    Ok((hash, complete_args))
+    Ok(complete_args)
 }
 
+/// Executes the operations in order on one host, so each sees the changes of those before it.
+/// Changes are committed only if every operation succeeds.
 fn execute_transaction(
-    args: &Vec<ScVal>,
+    operations: &[Operation],
     ledger_file: &PathBuf,
     commit: bool,
 ) -> Result<Value, Error> {
@@ -360,7 +447,16 @@ fn execute_transaction(
 
     // TODO: Check the parameters match the contract spec, or return a helpful error message
 
-    let res = h.invoke_function(HostFunction::Call, args.try_into()?)?;
+    let mut results = vec![];
+    for (index, operation) in operations.iter().enumerate() {
+        h.set_source_account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            operation.source_account,
+        ))));
+        let res = h
+            .invoke_function(operation.function.clone(), operation.parameters.clone())
+            .map_err(|error| Error::Operation { index, error })?;
+        results.push(json!({ "xdr": res.to_xdr_base64()? }));
+    }
 
     let (storage, budget, _) = h.try_finish().map_err(|_h| {
         HostError::from(ScStatus::HostStorageError(
@@ -405,9 +501,7 @@ fn execute_transaction(
             "readOnly": read_only,
             "readWrite": read_write,
         },
-        "results": vec![
-            json!({ "xdr": res.to_xdr_base64()? })
-        ],
+        "results": results,
         // TODO: Find "real" ledger seq number here
         "latestLedger": 1,
     }))
@@ -466,3 +560,202 @@ fn hash_bytes(b: Vec<u8>) -> [u8; 32] {
     output.copy_from_slice(&hasher.finalize());
     output
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use soroban_env_host::{
+        im_rc::OrdMap,
+        xdr::{
+            FeeBumpTransaction, FeeBumpTransactionEnvelope, FeeBumpTransactionExt, LedgerFootprint,
+            Memo, Operation as XdrOperation, Preconditions, SequenceNumber,
+            Transaction as XdrTransaction, TransactionExt, TransactionV1Envelope,
+        },
+    };
+
+    use super::*;
+    use crate::testutils;
+
+    const SOURCE: [u8; 32] = [1; 32];
+    const FEE_SOURCE: [u8; 32] = [2; 32];
+    const SALT: [u8; 32] = [3; 32];
+
+    /// A ledger file of its own for each test, removed if left over from an earlier run.
+    fn ledger_file(name: &str) -> PathBuf {
+        let file = std::env::temp_dir().join(format!(
+            "soroban-cli-serve-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&file);
+        file
+    }
+
+    fn bytes(b: &[u8]) -> ScVal {
+        ScVal::Object(Some(ScObject::Bytes(b.try_into().unwrap())))
+    }
+
+    fn operation(function: HostFunction, parameters: Vec<ScVal>) -> XdrOperation {
+        XdrOperation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                function,
+                parameters: parameters.try_into().unwrap(),
+                footprint: LedgerFootprint {
+                    read_only: vec![].try_into().unwrap(),
+                    read_write: vec![].try_into().unwrap(),
+                },
+            }),
+        }
+    }
+
+    fn create(contract: &[u8]) -> XdrOperation {
+        operation(
+            HostFunction::CreateContractWithSourceAccount,
+            vec![bytes(contract), bytes(&SALT)],
+        )
+    }
+
+    fn call(contract_id: [u8; 32], function: &str) -> XdrOperation {
+        operation(
+            HostFunction::Call,
+            vec![
+                bytes(&contract_id),
+                ScVal::Symbol(function.as_bytes().try_into().unwrap()),
+            ],
+        )
+    }
+
+    fn envelope(operations: Vec<XdrOperation>) -> TransactionV1Envelope {
+        TransactionV1Envelope {
+            tx: XdrTransaction {
+                source_account: MuxedAccount::Ed25519(Uint256(SOURCE)),
+                fee: 100,
+                seq_num: SequenceNumber(1),
+                cond: Preconditions::None,
+                memo: Memo::None,
+                operations: operations.try_into().unwrap(),
+                ext: TransactionExt::V0,
+            },
+            signatures: vec![].try_into().unwrap(),
+        }
+    }
+
+    fn parse(envelope: TransactionEnvelope) -> Transaction {
+        parse_transaction(
+            &envelope.to_xdr_base64().unwrap(),
+            SANDBOX_NETWORK_PASSPHRASE,
+        )
+        .unwrap()
+    }
+
+    fn result(value: &Value, index: usize) -> ScVal {
+        ScVal::from_xdr_base64(value["results"][index]["xdr"].as_str().unwrap().to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_operations_see_earlier_writes() {
+        let file = ledger_file("earlier-writes");
+        let contract = testutils::contract();
+        let contract_id = utils::contract_id_from_source_account(SOURCE, SALT).unwrap();
+        let txn = parse(TransactionEnvelope::Tx(envelope(vec![
+            create(&contract),
+            call(contract_id, "hello"),
+        ])));
+
+        let value = execute_transaction(&txn.operations, &file, true).unwrap();
+        assert_eq!(result(&value, 1), ScVal::U63(testutils::HELLO_RESULT));
+        assert_eq!(
+            utils::get_contract_wasm_from_ledger_file(&file, contract_id).unwrap(),
+            contract
+        );
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_failed_operation_commits_nothing() {
+        let file = ledger_file("failed-operation");
+        let mut entries = OrdMap::new();
+        utils::add_contract_to_ledger_entries(&mut entries, [9; 32], testutils::contract())
+            .unwrap();
+        snapshot::commit(entries, [], &file).unwrap();
+        let before = fs::read(&file).unwrap();
+
+        let contract_id = utils::contract_id_from_source_account(SOURCE, SALT).unwrap();
+        let txn = parse(TransactionEnvelope::Tx(envelope(vec![
+            create(&testutils::contract()),
+            call(contract_id, "missing"),
+        ])));
+
+        let err = execute_transaction(&txn.operations, &file, true).unwrap_err();
+        assert!(matches!(err, Error::Operation { index: 1, .. }));
+        assert_eq!(fs::read(&file).unwrap(), before);
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_fee_bump_runs_inner_transaction() {
+        let file = ledger_file("fee-bump");
+        let inner = envelope(vec![create(&testutils::contract())]);
+        let fee_bump = TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+            tx: FeeBumpTransaction {
+                fee_source: MuxedAccount::Ed25519(Uint256(FEE_SOURCE)),
+                fee: 200,
+                inner_tx: FeeBumpTransactionInnerTx::Tx(inner.clone()),
+                ext: FeeBumpTransactionExt::V0,
+            },
+            signatures: vec![].try_into().unwrap(),
+        });
+
+        let txn = parse(fee_bump);
+        let inner_txn = parse(TransactionEnvelope::Tx(inner));
+        assert_eq!(txn.inner_hash, Some(inner_txn.hash));
+        assert_ne!(txn.hash, inner_txn.hash);
+
+        // The contract ID comes from the inner transaction's source account, not the fee source
+        let value = execute_transaction(&txn.operations, &file, false).unwrap();
+        let contract_id = utils::contract_id_from_source_account(SOURCE, SALT).unwrap();
+        assert_eq!(result(&value, 0), bytes(&contract_id));
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_create_contract_id_from_operation_source_account() {
+        let file = ledger_file("create-source-account");
+        let mut op = create(&testutils::contract());
+        op.source_account = Some(MuxedAccount::Ed25519(Uint256(FEE_SOURCE)));
+        let txn = parse(TransactionEnvelope::Tx(envelope(vec![op])));
+
+        let value = execute_transaction(&txn.operations, &file, true).unwrap();
+        let contract_id = utils::contract_id_from_source_account(FEE_SOURCE, SALT).unwrap();
+        assert_eq!(result(&value, 0), bytes(&contract_id));
+        assert_eq!(
+            utils::get_contract_wasm_from_ledger_file(&file, contract_id).unwrap(),
+            testutils::contract()
+        );
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_create_contract_with_bad_ed25519_signature_commits_nothing() {
+        let file = ledger_file("create-ed25519");
+        // The ed25519 base point, a valid key that didn't sign anything here
+        let mut key = [0x66; 32];
+        key[0] = 0x58;
+        let txn = parse(TransactionEnvelope::Tx(envelope(vec![operation(
+            HostFunction::CreateContractWithEd25519,
+            vec![
+                bytes(&testutils::contract()),
+                bytes(&SALT),
+                bytes(&key),
+                bytes(&[0; 64]),
+            ],
+        )])));
+
+        let err = execute_transaction(&txn.operations, &file, true).unwrap_err();
+        assert!(matches!(err, Error::Operation { index: 0, .. }));
+        assert!(!file.exists());
+    }
+}
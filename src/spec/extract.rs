@@ -0,0 +1,53 @@
+use std::{fmt::Debug, fs, io, io::Write, path::PathBuf};
+
+use clap::Parser;
+use soroban_env_host::xdr;
+
+use super::Format;
+use crate::wasm;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    #[clap(flatten)]
+    wasm: wasm::Args,
+    /// Format to write the spec in
+    #[clap(long, arg_enum, default_value = "xdr")]
+    format: Format,
+    /// File to write the spec to, instead of stdout
+    #[clap(long, parse(from_os_str))]
+    out: Option<PathBuf>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("parsing wasm: {0}")]
+    WasmParse(#[from] wasmparser::BinaryReaderError),
+    #[error("contract has no contractspecv0 section")]
+    MissingSpec,
+    #[error("xdr")]
+    Xdr(#[from] xdr::Error),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
+    #[error("writing spec: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contents = self.wasm.read()?;
+        let module = wasm::parse(&contents)?;
+        let spec = module.spec.ok_or(Error::MissingSpec)?;
+
+        let out = match self.format {
+            Format::Xdr => spec.to_vec(),
+            Format::Json => serde_json::to_vec_pretty(&super::decode(spec)?)?,
+        };
+        match &self.out {
+            Some(path) => fs::write(path, out)?,
+            None => io::stdout().write_all(&out)?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use clap::{ArgEnum, Subcommand};
+use soroban_env_host::xdr::{self, ReadXdr, ScSpecEntry, WriteXdr};
+
+pub mod extract;
+pub mod set;
+
+#[derive(Subcommand, Debug)]
+pub enum Cmd {
+    /// Write the contract spec of a WASM file as xdr or json
+    Extract(extract::Cmd),
+    /// Embed a contract spec in a WASM file, replacing any existing spec
+    Set(set::Cmd),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Extract(#[from] extract::Error),
+    #[error(transparent)]
+    Set(#[from] set::Error),
+}
+
+/// Format of a contract spec file.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Format {
+    /// Stream of xdr-encoded ScSpecEntry, as stored in the contractspecv0 section
+    Xdr,
+    /// Json array of ScSpecEntry
+    Json,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        match self {
+            Cmd::Extract(extract) => extract.run()?,
+            Cmd::Set(set) => set.run()?,
+        };
+        Ok(())
+    }
+}
+
+pub fn encode(entries: &[ScSpecEntry]) -> Result<Vec<u8>, xdr::Error> {
+    let mut spec = vec![];
+    for entry in entries {
+        spec.extend(entry.to_xdr()?);
+    }
+    Ok(spec)
+}
+
+pub fn decode(spec: &[u8]) -> Result<Vec<ScSpecEntry>, xdr::Error> {
+    let mut cursor = std::io::Cursor::new(spec);
+    ScSpecEntry::read_xdr_iter(&mut cursor).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use clap::Parser;
+    use soroban_env_host::{
+        xdr::{ScSpecTypeDef, ScSpecUdtStructFieldV0, ScSpecUdtStructV0},
+        Host, Vm,
+    };
+
+    use super::*;
+    use crate::testutils;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("soroban-cli-spec-{}-{}", std::process::id(), name))
+    }
+
+    fn entries() -> Vec<ScSpecEntry> {
+        let mut entries = testutils::spec();
+        entries.push(ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+            name: "Point".as_bytes().try_into().unwrap(),
+            fields: vec![ScSpecUdtStructFieldV0 {
+                name: "x".as_bytes().try_into().unwrap(),
+                type_: ScSpecTypeDef::U32,
+            }]
+            .try_into()
+            .unwrap(),
+        }));
+        entries
+    }
+
+    /// Embeds the spec file in the test contract with `set`, extracts it again with `extract`,
+    /// and checks the contract still loads.
+    fn set_and_extract(format: &str, spec: &[u8]) -> Vec<u8> {
+        let wasm = temp_file(&format!("{}.wasm", format));
+        let spec_file = temp_file(&format!("{}.spec", format));
+        let out = temp_file(&format!("{}.out.wasm", format));
+        let extracted = temp_file(&format!("{}.extracted", format));
+        fs::write(&wasm, testutils::contract()).unwrap();
+        fs::write(&spec_file, spec).unwrap();
+
+        set::Cmd::parse_from([
+            "set",
+            "--wasm",
+            wasm.to_str().unwrap(),
+            "--spec",
+            spec_file.to_str().unwrap(),
+            "--format",
+            format,
+            "--out",
+            out.to_str().unwrap(),
+        ])
+        .run()
+        .unwrap();
+        extract::Cmd::parse_from([
+            "extract",
+            "--wasm",
+            out.to_str().unwrap(),
+            "--format",
+            format,
+            "--out",
+            extracted.to_str().unwrap(),
+        ])
+        .run()
+        .unwrap();
+
+        let patched = fs::read(&out).unwrap();
+        Vm::new(&Host::default(), [0; 32].into(), &patched).unwrap();
+        let result = fs::read(&extracted).unwrap();
+        for file in [wasm, spec_file, out, extracted] {
+            fs::remove_file(file).unwrap();
+        }
+        result
+    }
+
+    #[test]
+    fn test_xdr_round_trip() {
+        let spec = encode(&entries()).unwrap();
+        assert_eq!(set_and_extract("xdr", &spec), spec);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let spec = serde_json::to_vec(&entries()).unwrap();
+        let extracted = set_and_extract("json", &spec);
+        assert_eq!(
+            serde_json::from_slice::<Vec<ScSpecEntry>>(&extracted).unwrap(),
+            entries()
+        );
+    }
+}
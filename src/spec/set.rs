@@ -0,0 +1,94 @@
+use std::{fmt::Debug, fs, io, path::PathBuf};
+
+use clap::Parser;
+use soroban_env_host::xdr::{self, ScSpecEntry};
+
+use super::Format;
+use crate::wasm;
+
+const SPEC_SECTION: &str = "contractspecv0";
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    #[clap(flatten)]
+    wasm: wasm::Args,
+    /// File containing the spec to embed
+    #[clap(long, parse(from_os_str))]
+    spec: PathBuf,
+    /// Format of the spec file
+    #[clap(long, arg_enum, default_value = "xdr")]
+    format: Format,
+    /// File to write the WASM with the new spec to
+    #[clap(long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Wasm(#[from] wasm::Error),
+    #[error("parsing wasm: {0}")]
+    WasmParse(#[from] wasmparser::BinaryReaderError),
+    #[error("malformed wasm module")]
+    Malformed,
+    #[error("reading file {filepath}: {error}")]
+    CannotReadSpecFile { filepath: PathBuf, error: io::Error },
+    #[error("parsing spec: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error("parsing spec: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("embedded spec does not match the spec file")]
+    SpecMismatch,
+    #[error("writing file {filepath}: {error}")]
+    CannotWriteContractFile { filepath: PathBuf, error: io::Error },
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let contents = self.wasm.read()?;
+        let entries = self.read_spec()?;
+        // Re-encode the entries so that the embedded section is always valid xdr
+        let spec = super::encode(&entries)?;
+        let spec_section = wasm::RawSection::custom(SPEC_SECTION, &spec);
+
+        let sections = wasm::raw_sections(&contents).ok_or(Error::Malformed)?;
+        let mut replaced = false;
+        let mut out: Vec<(u8, &[u8])> = vec![];
+        for s in &sections {
+            if s.custom_name() == Some(SPEC_SECTION) {
+                // The spec replaces the first spec section, and any others are dropped so that
+                // there's no other spec for readers to find
+                if !replaced {
+                    out.push((s.id, spec_section.as_slice()));
+                    replaced = true;
+                }
+            } else {
+                out.push((s.id, s.contents));
+            }
+        }
+        if !replaced {
+            out.push((0, spec_section.as_slice()));
+        }
+        let patched = wasm::encode_sections(&contents, out);
+
+        if wasm::parse(&patched)?.spec_entries()? != entries {
+            return Err(Error::SpecMismatch);
+        }
+        fs::write(&self.out, patched).map_err(|e| Error::CannotWriteContractFile {
+            filepath: self.out.clone(),
+            error: e,
+        })?;
+        Ok(())
+    }
+
+    fn read_spec(&self) -> Result<Vec<ScSpecEntry>, Error> {
+        let contents = fs::read(&self.spec).map_err(|e| Error::CannotReadSpecFile {
+            filepath: self.spec.clone(),
+            error: e,
+        })?;
+        Ok(match self.format {
+            Format::Xdr => super::decode(&contents)?,
+            Format::Json => serde_json::from_slice(&contents)?,
+        })
+    }
+}
@@ -0,0 +1,386 @@
+use std::{collections::BTreeMap, fmt::Debug, fs, io, path::PathBuf};
+
+use clap::{ArgEnum, Parser};
+use serde_json::json;
+use soroban_env_host::xdr::{
+    self, ScSpecEntry, ScSpecFunctionV0, ScSpecUdtStructV0, ScSpecUdtUnionV0,
+};
+
+use crate::contractspec::{self, type_to_string};
+use crate::wasm;
+
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// WASM file of the released contract
+    #[clap(parse(from_os_str))]
+    old: PathBuf,
+    /// WASM file of the new contract
+    #[clap(parse(from_os_str))]
+    new: PathBuf,
+    /// Type of output to generate
+    #[clap(long, arg_enum, default_value = "text")]
+    output: Output,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ArgEnum)]
+pub enum Output {
+    /// Human readable listing of the changes
+    Text,
+    /// Json document of the changes
+    Json,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("reading file {filepath}: {error}")]
+    CannotReadContractFile { filepath: PathBuf, error: io::Error },
+    #[error("parsing wasm {filepath}: {error}")]
+    WasmParse {
+        filepath: PathBuf,
+        error: wasmparser::BinaryReaderError,
+    },
+    #[error("xdr")]
+    Xdr(#[from] xdr::Error),
+    #[error("serde")]
+    Serde(#[from] serde_json::Error),
+    #[error("{0} breaking change(s) found")]
+    BreakingChanges(usize),
+}
+
+/// A difference between two contract specs, and whether it breaks existing callers.
+pub struct Change {
+    pub breaking: bool,
+    /// The spec entry that changed, e.g. `fn transfer`
+    pub entry: String,
+    pub description: String,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let changes = diff(&read_spec(&self.old)?, &read_spec(&self.new)?);
+        match self.output {
+            Output::Text => {
+                for c in &changes {
+                    let kind = if c.breaking {
+                        "breaking"
+                    } else {
+                        "non-breaking"
+                    };
+                    println!("{}: {}: {}", kind, c.entry, c.description);
+                }
+            }
+            Output::Json => {
+                let changes = changes
+                    .iter()
+                    .map(|c| {
+                        json!({
+                            "breaking": c.breaking,
+                            "entry": c.entry,
+                            "description": c.description,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&changes)?);
+            }
+        }
+
+        let breaking = changes.iter().filter(|c| c.breaking).count();
+        if breaking > 0 {
+            return Err(Error::BreakingChanges(breaking));
+        }
+        Ok(())
+    }
+}
+
+fn read_spec(filepath: &PathBuf) -> Result<Vec<ScSpecEntry>, Error> {
+    let contents = fs::read(filepath).map_err(|e| Error::CannotReadContractFile {
+        filepath: filepath.clone(),
+        error: e,
+    })?;
+    let module = wasm::parse(&contents).map_err(|e| Error::WasmParse {
+        filepath: filepath.clone(),
+        error: e,
+    })?;
+    Ok(module.spec_entries()?)
+}
+
+/// Compares the entries of two contract specs. Removing or changing anything a caller may
+/// depend on is breaking, while additions are not.
+pub fn diff(old: &[ScSpecEntry], new: &[ScSpecEntry]) -> Vec<Change> {
+    let named = |entries: &[ScSpecEntry]| -> BTreeMap<String, ScSpecEntry> {
+        entries
+            .iter()
+            .map(|e| (contractspec::entry_name(e), e.clone()))
+            .collect()
+    };
+    let old = named(old);
+    let new = named(new);
+
+    let mut changes = vec![];
+    for (name, old_entry) in &old {
+        let mut change = |breaking: bool, description: String| {
+            changes.push(Change {
+                breaking,
+                entry: name.clone(),
+                description,
+            });
+        };
+        match (old_entry, new.get(name)) {
+            (_, None) => change(true, "removed".to_string()),
+            (ScSpecEntry::FunctionV0(o), Some(ScSpecEntry::FunctionV0(n))) => {
+                diff_function(o, n, &mut change);
+            }
+            (ScSpecEntry::UdtStructV0(o), Some(ScSpecEntry::UdtStructV0(n))) => {
+                diff_struct(o, n, &mut change);
+            }
+            (ScSpecEntry::UdtUnionV0(o), Some(ScSpecEntry::UdtUnionV0(n))) => {
+                diff_union(o, n, &mut change);
+            }
+            // Entries are keyed by kind and name, so both sides are always the same kind
+            (_, Some(_)) => {}
+        }
+    }
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        changes.push(Change {
+            breaking: false,
+            entry: name.clone(),
+            description: "added".to_string(),
+        });
+    }
+    changes
+}
+
+fn diff_function(
+    old: &ScSpecFunctionV0,
+    new: &ScSpecFunctionV0,
+    change: &mut impl FnMut(bool, String),
+) {
+    if old.inputs.len() != new.inputs.len() {
+        change(
+            true,
+            format!(
+                "parameter count changed from {} to {}",
+                old.inputs.len(),
+                new.inputs.len()
+            ),
+        );
+    }
+    // Arguments are passed by position, so a type change at any position is breaking even when
+    // it comes from reordering parameters. A rename alone is not, but a name moving to another
+    // position is, as callers passing values of the same type would silently swap them.
+    for (i, (o, n)) in old.inputs.iter().zip(new.inputs.iter()).enumerate() {
+        let (o_name, n_name) = (o.name.to_string_lossy(), n.name.to_string_lossy());
+        if o.type_ != n.type_ {
+            change(
+                true,
+                format!(
+                    "parameter {} ({}) changed type from {} to {}",
+                    i,
+                    n_name,
+                    type_to_string(&o.type_),
+                    type_to_string(&n.type_)
+                ),
+            );
+        } else if o_name != n_name {
+            match new
+                .inputs
+                .iter()
+                .position(|p| p.name.to_string_lossy() == o_name)
+            {
+                Some(j) => change(
+                    true,
+                    format!("parameter {} moved from position {} to {}", o_name, i, j),
+                ),
+                None => change(
+                    false,
+                    format!("parameter {} renamed from {} to {}", i, o_name, n_name),
+                ),
+            }
+        }
+    }
+    if old.outputs != new.outputs {
+        change(
+            true,
+            format!(
+                "return type changed from {} to {}",
+                contractspec::tuple_to_string(old.outputs.as_slice()),
+                contractspec::tuple_to_string(new.outputs.as_slice())
+            ),
+        );
+    }
+}
+
+fn diff_struct(
+    old: &ScSpecUdtStructV0,
+    new: &ScSpecUdtStructV0,
+    change: &mut impl FnMut(bool, String),
+) {
+    let new_fields: BTreeMap<String, _> = new
+        .fields
+        .iter()
+        .map(|f| (f.name.to_string_lossy(), &f.type_))
+        .collect();
+    for o in old.fields.iter() {
+        let name = o.name.to_string_lossy();
+        match new_fields.get(&name) {
+            None => change(true, format!("field {} removed", name)),
+            Some(t) if **t != o.type_ => change(
+                true,
+                format!(
+                    "field {} changed type from {} to {}",
+                    name,
+                    type_to_string(&o.type_),
+                    type_to_string(t)
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+    // Structs are encoded with all of their fields, so values built by existing callers won't
+    // decode once a field is added.
+    for n in new.fields.iter() {
+        let name = n.name.to_string_lossy();
+        if !old.fields.iter().any(|o| o.name.to_string_lossy() == name) {
+            change(true, format!("field {} added", name));
+        }
+    }
+}
+
+fn diff_union(
+    old: &ScSpecUdtUnionV0,
+    new: &ScSpecUdtUnionV0,
+    change: &mut impl FnMut(bool, String),
+) {
+    let new_cases: BTreeMap<String, _> = new
+        .cases
+        .iter()
+        .map(|c| (c.name.to_string_lossy(), &c.type_))
+        .collect();
+    for o in old.cases.iter() {
+        let name = o.name.to_string_lossy();
+        match new_cases.get(&name) {
+            None => change(true, format!("variant {} removed", name)),
+            Some(t) if **t != o.type_ => change(
+                true,
+                format!(
+                    "variant {} changed type from {} to {}",
+                    name,
+                    o.type_.as_ref().map_or("()".to_string(), type_to_string),
+                    t.as_ref().map_or("()".to_string(), type_to_string)
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+    for n in new.cases.iter() {
+        let name = n.name.to_string_lossy();
+        if !old.cases.iter().any(|o| o.name.to_string_lossy() == name) {
+            change(false, format!("variant {} added", name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_env_host::xdr::{ScSpecFunctionInputV0, ScSpecTypeDef};
+
+    use super::*;
+
+    fn function(
+        name: &str,
+        inputs: &[(&str, ScSpecTypeDef)],
+        outputs: &[ScSpecTypeDef],
+    ) -> ScSpecEntry {
+        ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+            name: name.as_bytes().try_into().unwrap(),
+            inputs: inputs
+                .iter()
+                .map(|(n, t)| ScSpecFunctionInputV0 {
+                    name: n.as_bytes().try_into().unwrap(),
+                    type_: t.clone(),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            outputs: outputs.to_vec().try_into().unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_diff() {
+        use ScSpecTypeDef::{Symbol, I32, U32};
+        let cases: Vec<(&str, Vec<ScSpecEntry>, Vec<ScSpecEntry>, Vec<(bool, &str)>)> = vec![
+            (
+                "unchanged",
+                vec![function("f", &[("a", U32)], &[])],
+                vec![function("f", &[("a", U32)], &[])],
+                vec![],
+            ),
+            (
+                "add",
+                vec![],
+                vec![function("f", &[], &[])],
+                vec![(false, "added")],
+            ),
+            (
+                "remove",
+                vec![function("f", &[], &[])],
+                vec![],
+                vec![(true, "removed")],
+            ),
+            (
+                "retype",
+                vec![function("f", &[("a", U32)], &[])],
+                vec![function("f", &[("a", I32)], &[])],
+                vec![(true, "parameter 0 (a) changed type from u32 to i32")],
+            ),
+            (
+                "return type",
+                vec![function("f", &[], &[U32])],
+                vec![function("f", &[], &[Symbol])],
+                vec![(true, "return type changed from u32 to Symbol")],
+            ),
+            (
+                "parameter count",
+                vec![function("f", &[("a", U32)], &[])],
+                vec![function("f", &[("a", U32), ("b", U32)], &[])],
+                vec![(true, "parameter count changed from 1 to 2")],
+            ),
+            (
+                "rename",
+                vec![function("f", &[("a", U32)], &[])],
+                vec![function("f", &[("b", U32)], &[])],
+                vec![(false, "parameter 0 renamed from a to b")],
+            ),
+            (
+                "reorder same type",
+                vec![function("f", &[("a", U32), ("b", U32)], &[])],
+                vec![function("f", &[("b", U32), ("a", U32)], &[])],
+                vec![
+                    (true, "parameter a moved from position 0 to 1"),
+                    (true, "parameter b moved from position 1 to 0"),
+                ],
+            ),
+            (
+                "reorder different types",
+                vec![function("f", &[("a", U32), ("b", I32)], &[])],
+                vec![function("f", &[("b", I32), ("a", U32)], &[])],
+                vec![
+                    (true, "parameter 0 (b) changed type from u32 to i32"),
+                    (true, "parameter 1 (a) changed type from i32 to u32"),
+                ],
+            ),
+        ];
+        for (name, old, new, expected) in cases {
+            let changes = diff(&old, &new)
+                .into_iter()
+                .map(|c| (c.breaking, c.description))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .into_iter()
+                .map(|(b, d)| (b, d.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(changes, expected, "{}", name);
+        }
+    }
+}
@@ -3,12 +3,15 @@ use std::{error::Error, fmt::Display, str::FromStr};
 
 use num_bigint::{BigInt, Sign};
 use soroban_env_host::xdr::{
-    Error as XdrError, ScBigInt, ScMap, ScMapEntry, ScObject, ScSpecTypeDef, ScSpecTypeMap,
-    ScSpecTypeOption, ScSpecTypeTuple, ScSpecTypeVec, ScStatic, ScVal, ScVec, VecM,
+    Error as XdrError, ScBigInt, ScMap, ScMapEntry, ScObject, ScSpecEntry, ScSpecTypeDef,
+    ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+    ScSpecUdtStructV0, ScSpecUdtUnionV0, ScStatic, ScVal, ScVec, VecM,
 };
 
 use stellar_strkey::StrkeyPublicKeyEd25519;
 
+use crate::contractspec;
+
 #[derive(Debug)]
 pub enum StrValError {
     UnknownError,
@@ -48,7 +51,9 @@ impl From<()> for StrValError {
     }
 }
 
-pub fn from_string(s: &str, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
+/// Parses a value of the type. User defined types are looked up in `spec`, which may be empty
+/// when the type can't refer to any.
+pub fn from_string(s: &str, t: &ScSpecTypeDef, spec: &[ScSpecEntry]) -> Result<ScVal, StrValError> {
     let val: ScVal = match t {
         // These ones have special processing when they're the top-level args. This is so we don't
         // need extra quotes around string args.
@@ -62,7 +67,7 @@ pub fn from_string(s: &str, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
         ScSpecTypeDef::Bytes => {
             match serde_json::from_str(s) {
                 // First, see if it is a json array
-                Ok(Value::Array(raw)) => from_json(&Value::Array(raw), t)?,
+                Ok(Value::Array(raw)) => from_json(&Value::Array(raw), t, spec)?,
                 _ =>
                 // it could be a G- strkey
                 {
@@ -88,7 +93,7 @@ pub fn from_string(s: &str, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
         ScSpecTypeDef::BigInt => {
             if let Ok(Value::String(raw)) = serde_json::from_str(s) {
                 // First, see if it is a json string, strip the quotes and recurse
-                from_string(&raw, &ScSpecTypeDef::BigInt)?
+                from_string(&raw, &ScSpecTypeDef::BigInt, spec)?
             } else {
                 let big = BigInt::from_str(s).map_err(|_| StrValError::InvalidValue)?;
                 let (sign, bytes) = big.to_bytes_be();
@@ -102,15 +107,20 @@ pub fn from_string(s: &str, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
         }
 
         // For all others we just use the json parser
-        _ => serde_json::from_str(s)
-            .map_err(StrValError::Serde)
-            .and_then(|raw| from_json(&raw, t))?,
+        _ => match serde_json::from_str(s) {
+            Ok(raw) => from_json(&raw, t, spec)?,
+            // Union cases without a value can be given without quotes, e.g. Admin
+            Err(_) if matches!(t, ScSpecTypeDef::Udt(_)) => {
+                from_json(&Value::String(s.to_string()), t, spec)?
+            }
+            Err(e) => return Err(StrValError::Serde(e)),
+        },
     };
     Ok(val)
 }
 
 #[allow(clippy::too_many_lines)]
-pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
+pub fn from_json(v: &Value, t: &ScSpecTypeDef, spec: &[ScSpecEntry]) -> Result<ScVal, StrValError> {
     let val: ScVal = match (t, v) {
         // Boolean parsing
         (ScSpecTypeDef::Bool, Value::Bool(true)) => ScVal::Static(ScStatic::True),
@@ -121,17 +131,30 @@ pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
             let ScSpecTypeVec { element_type } = &**elem;
             let parsed: Result<Vec<ScVal>, StrValError> = raw
                 .iter()
-                .map(|item| -> Result<ScVal, StrValError> { from_json(item, element_type) })
+                .map(|item| -> Result<ScVal, StrValError> { from_json(item, element_type, spec) })
                 .collect();
             let converted: ScVec = parsed?.try_into().map_err(StrValError::Xdr)?;
             ScVal::Object(Some(ScObject::Vec(converted)))
         }
 
-        // Number parsing
-        (ScSpecTypeDef::BigInt, Value::String(s)) => from_string(s, &ScSpecTypeDef::BigInt)?,
-        (ScSpecTypeDef::BigInt, Value::Number(n)) => {
-            from_json(&Value::String(format!("{}", n)), &ScSpecTypeDef::BigInt)?
+        // Set parsing, sets are passed as vecs of their elements
+        (ScSpecTypeDef::Set(elem), Value::Array(raw)) => {
+            let ScSpecTypeSet { element_type } = &**elem;
+            let parsed: Result<Vec<ScVal>, StrValError> = raw
+                .iter()
+                .map(|item| from_json(item, element_type, spec))
+                .collect();
+            let converted: ScVec = parsed?.try_into().map_err(StrValError::Xdr)?;
+            ScVal::Object(Some(ScObject::Vec(converted)))
         }
+
+        // Number parsing
+        (ScSpecTypeDef::BigInt, Value::String(s)) => from_string(s, &ScSpecTypeDef::BigInt, spec)?,
+        (ScSpecTypeDef::BigInt, Value::Number(n)) => from_json(
+            &Value::String(format!("{}", n)),
+            &ScSpecTypeDef::BigInt,
+            spec,
+        )?,
         (ScSpecTypeDef::I32, Value::Number(n)) => ScVal::I32(
             n.as_i64()
                 .ok_or(StrValError::InvalidValue)?
@@ -150,6 +173,9 @@ pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
         (ScSpecTypeDef::U64, Value::Number(n)) => {
             ScVal::U63(n.as_i64().ok_or(StrValError::InvalidValue)?)
         }
+        (ScSpecTypeDef::Bitset, Value::Number(n)) => {
+            ScVal::Bitset(n.as_u64().ok_or(StrValError::InvalidValue)?)
+        }
 
         // Map parsing
         (ScSpecTypeDef::Map(map), Value::Object(raw)) => {
@@ -161,8 +187,8 @@ pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
             let parsed: Result<Vec<ScMapEntry>, StrValError> = raw
                 .iter()
                 .map(|(k, v)| -> Result<ScMapEntry, StrValError> {
-                    let key = from_string(k, key_type)?;
-                    let val = from_json(v, value_type)?;
+                    let key = from_string(k, key_type, spec)?;
+                    let val = from_json(v, value_type, spec)?;
                     Ok(ScMapEntry { key, val })
                 })
                 .collect();
@@ -198,16 +224,12 @@ pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
             ScVal::Object(Some(ScObject::Bytes(converted)))
         }
 
-        // Option parsing
+        // Option parsing, with Some values passed as the value itself
         // is null -> void the right thing here?
         (ScSpecTypeDef::Option(_), Value::Null) => ScVal::Object(None),
         (ScSpecTypeDef::Option(elem), v) => {
             let ScSpecTypeOption { value_type } = &**elem;
-            ScVal::Object(Some(
-                from_json(v, value_type)?
-                    .try_into()
-                    .map_err(|_| StrValError::InvalidValue)?,
-            ))
+            from_json(v, value_type, spec)?
         }
 
         // Tuple parsing
@@ -219,23 +241,221 @@ pub fn from_json(v: &Value, t: &ScSpecTypeDef) -> Result<ScVal, StrValError> {
             let parsed: Result<Vec<ScVal>, StrValError> = raw
                 .iter()
                 .zip(value_types.iter())
-                .map(|(item, t)| from_json(item, t))
+                .map(|(item, t)| from_json(item, t, spec))
                 .collect();
             let converted: ScVec = parsed?.try_into().map_err(StrValError::Xdr)?;
             ScVal::Object(Some(ScObject::Vec(converted)))
         }
 
+        // User defined type parsing, in the forms `gen --output json-schema` describes
+        (ScSpecTypeDef::Udt(ScSpecTypeUdt { name }), raw) => {
+            match contractspec::find_udt(spec, &name.to_string_lossy()) {
+                Some(ScSpecEntry::UdtStructV0(s)) => struct_from_json(raw, s, spec)?,
+                Some(ScSpecEntry::UdtUnionV0(u)) => union_from_json(raw, u, spec)?,
+                _ => serde_json::from_value(raw.clone()).map_err(StrValError::Serde)?,
+            }
+        }
+
         // TODO: Implement the rest of these
-        // ScSpecTypeDef::Bitset => {},
         // ScSpecTypeDef::Status => {},
         // ScSpecTypeDef::Result(Box<ScSpecTypeResult>) => {},
-        // ScSpecTypeDef::Set(Box<ScSpecTypeSet>) => {},
-        // ScSpecTypeDef::Udt(ScSpecTypeUdt) => {},
         (_, raw) => serde_json::from_value(raw.clone()).map_err(StrValError::Serde)?,
     };
     Ok(val)
 }
 
+/// Parses a struct from an object with every field, into a map keyed by field name the way the
+/// SDK encodes structs.
+fn struct_from_json(
+    v: &Value,
+    s: &ScSpecUdtStructV0,
+    spec: &[ScSpecEntry],
+) -> Result<ScVal, StrValError> {
+    let raw = v.as_object().ok_or(StrValError::InvalidValue)?;
+    if raw.len() != s.fields.len() {
+        return Err(StrValError::InvalidValue);
+    }
+    let parsed: Result<Vec<ScMapEntry>, StrValError> = s
+        .fields
+        .iter()
+        .map(|f| {
+            let name = f.name.to_string_lossy();
+            let val = raw.get(&name).ok_or(StrValError::InvalidValue)?;
+            Ok(ScMapEntry {
+                key: ScVal::Symbol(
+                    name.as_bytes()
+                        .try_into()
+                        .map_err(|_| StrValError::InvalidValue)?,
+                ),
+                val: from_json(val, &f.type_, spec)?,
+            })
+        })
+        .collect();
+    Ok(ScVal::Object(Some(ScObject::Map(
+        ScMap::sorted_from(parsed?).map_err(StrValError::Xdr)?,
+    ))))
+}
+
+/// Parses a union case from its name, e.g. `"Admin"`, or from an object with the case name as
+/// its only key, e.g. `{"Balance": 1}`, into a vec of the case name and value the way the SDK
+/// encodes unions.
+fn union_from_json(
+    v: &Value,
+    u: &ScSpecUdtUnionV0,
+    spec: &[ScSpecEntry],
+) -> Result<ScVal, StrValError> {
+    let (case_name, value) = match v {
+        Value::String(s) => (s.as_str(), None),
+        Value::Object(o) if o.len() == 1 => match o.iter().next() {
+            Some((k, v)) => (k.as_str(), Some(v)),
+            None => return Err(StrValError::InvalidValue),
+        },
+        _ => return Err(StrValError::InvalidValue),
+    };
+    let case = u
+        .cases
+        .iter()
+        .find(|c| c.name.to_string_lossy() == case_name)
+        .ok_or(StrValError::InvalidValue)?;
+    let mut values = vec![ScVal::Symbol(
+        case_name
+            .as_bytes()
+            .try_into()
+            .map_err(|_| StrValError::InvalidValue)?,
+    )];
+    match (&case.type_, value) {
+        (None, None) => {}
+        (Some(t), Some(v)) => values.push(from_json(v, t, spec)?),
+        _ => return Err(StrValError::InvalidValue),
+    }
+    let converted: ScVec = values.try_into().map_err(StrValError::Xdr)?;
+    Ok(ScVal::Object(Some(ScObject::Vec(converted))))
+}
+
+/// The JSON form of a value of the type, the inverse of `from_json`.
+pub fn to_json_of_type(
+    v: &ScVal,
+    t: &ScSpecTypeDef,
+    spec: &[ScSpecEntry],
+) -> Result<Value, StrValError> {
+    let items = |items: &ScVec, t: &ScSpecTypeDef| -> Result<Value, StrValError> {
+        let values: Result<Vec<Value>, StrValError> = items
+            .iter()
+            .map(|item| to_json_of_type(item, t, spec))
+            .collect();
+        Ok(Value::Array(values?))
+    };
+    let val: Value = match (t, v) {
+        (ScSpecTypeDef::Option(_), ScVal::Object(None)) => Value::Null,
+        (ScSpecTypeDef::Option(elem), v) => {
+            let ScSpecTypeOption { value_type } = &**elem;
+            to_json_of_type(v, value_type, spec)?
+        }
+        (ScSpecTypeDef::Vec(elem), ScVal::Object(Some(ScObject::Vec(v)))) => {
+            let ScSpecTypeVec { element_type } = &**elem;
+            items(v, element_type)?
+        }
+        (ScSpecTypeDef::Set(elem), ScVal::Object(Some(ScObject::Vec(v)))) => {
+            let ScSpecTypeSet { element_type } = &**elem;
+            items(v, element_type)?
+        }
+        (ScSpecTypeDef::Tuple(elem), ScVal::Object(Some(ScObject::Vec(v)))) => {
+            let ScSpecTypeTuple { value_types } = &**elem;
+            if v.len() != value_types.len() {
+                return Err(StrValError::InvalidValue);
+            }
+            let values: Result<Vec<Value>, StrValError> = v
+                .iter()
+                .zip(value_types.iter())
+                .map(|(item, t)| to_json_of_type(item, t, spec))
+                .collect();
+            Value::Array(values?)
+        }
+        // Map keys are written the way `from_string` reads them
+        (ScSpecTypeDef::Map(map), ScVal::Object(Some(ScObject::Map(v)))) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = &**map;
+            let mut m = serde_json::Map::<String, Value>::with_capacity(v.len());
+            for ScMapEntry { key, val } in v.iter() {
+                let k = match to_json_of_type(key, key_type, spec)? {
+                    Value::String(s) => s,
+                    k => k.to_string(),
+                };
+                m.insert(k, to_json_of_type(val, value_type, spec)?);
+            }
+            Value::Object(m)
+        }
+        (ScSpecTypeDef::Bitset, ScVal::Bitset(b)) => Value::Number(serde_json::Number::from(*b)),
+        (ScSpecTypeDef::Udt(ScSpecTypeUdt { name }), v) => {
+            match contractspec::find_udt(spec, &name.to_string_lossy()) {
+                Some(ScSpecEntry::UdtStructV0(s)) => struct_to_json(v, s, spec)?,
+                Some(ScSpecEntry::UdtUnionV0(u)) => union_to_json(v, u, spec)?,
+                _ => return Err(StrValError::InvalidValue),
+            }
+        }
+        // These can't be read by `from_json`
+        (ScSpecTypeDef::Status | ScSpecTypeDef::Result(_), _) => {
+            return Err(StrValError::InvalidValue)
+        }
+        (_, v) => to_json(v)?,
+    };
+    Ok(val)
+}
+
+fn struct_to_json(
+    v: &ScVal,
+    s: &ScSpecUdtStructV0,
+    spec: &[ScSpecEntry],
+) -> Result<Value, StrValError> {
+    let map = match v {
+        ScVal::Object(Some(ScObject::Map(map))) if map.len() == s.fields.len() => map,
+        _ => return Err(StrValError::InvalidValue),
+    };
+    let mut m = serde_json::Map::<String, Value>::with_capacity(map.len());
+    for f in s.fields.iter() {
+        let name = f.name.to_string_lossy();
+        let entry = map
+            .iter()
+            .find(|e| matches!(&e.key, ScVal::Symbol(k) if k.as_slice() == name.as_bytes()))
+            .ok_or(StrValError::InvalidValue)?;
+        m.insert(name, to_json_of_type(&entry.val, &f.type_, spec)?);
+    }
+    Ok(Value::Object(m))
+}
+
+fn union_to_json(
+    v: &ScVal,
+    u: &ScSpecUdtUnionV0,
+    spec: &[ScSpecEntry],
+) -> Result<Value, StrValError> {
+    let values = match v {
+        ScVal::Object(Some(ScObject::Vec(values))) => values.as_slice(),
+        _ => return Err(StrValError::InvalidValue),
+    };
+    let case_name = match values.first() {
+        Some(ScVal::Symbol(s)) => {
+            std::str::from_utf8(s.as_slice()).map_err(|_| StrValError::InvalidValue)?
+        }
+        _ => return Err(StrValError::InvalidValue),
+    };
+    let case = u
+        .cases
+        .iter()
+        .find(|c| c.name.to_string_lossy() == case_name)
+        .ok_or(StrValError::InvalidValue)?;
+    Ok(match (&case.type_, &values[1..]) {
+        (None, []) => Value::String(case_name.to_string()),
+        (Some(t), [value]) => {
+            let mut m = serde_json::Map::<String, Value>::with_capacity(1);
+            m.insert(case_name.to_string(), to_json_of_type(value, t, spec)?);
+            Value::Object(m)
+        }
+        _ => return Err(StrValError::InvalidValue),
+    })
+}
+
 pub fn to_string(v: &ScVal) -> Result<String, StrValError> {
     #[allow(clippy::match_same_arms)]
     Ok(match v {
@@ -314,3 +534,177 @@ pub fn to_json(v: &ScVal) -> Result<Value, StrValError> {
     };
     Ok(val)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use soroban_env_host::xdr::{ScSpecUdtStructFieldV0, ScSpecUdtUnionCaseV0};
+
+    fn symbol(s: &str) -> ScVal {
+        ScVal::Symbol(s.as_bytes().try_into().unwrap())
+    }
+
+    fn vec(values: Vec<ScVal>) -> ScVal {
+        ScVal::Object(Some(ScObject::Vec(values.try_into().unwrap())))
+    }
+
+    fn udt(name: &str) -> ScSpecTypeDef {
+        ScSpecTypeDef::Udt(ScSpecTypeUdt {
+            name: name.as_bytes().try_into().unwrap(),
+        })
+    }
+
+    fn spec() -> Vec<ScSpecEntry> {
+        vec![
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                name: "Point".as_bytes().try_into().unwrap(),
+                fields: vec![
+                    ScSpecUdtStructFieldV0 {
+                        name: "y".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::U32,
+                    },
+                    ScSpecUdtStructFieldV0 {
+                        name: "x".as_bytes().try_into().unwrap(),
+                        type_: ScSpecTypeDef::U32,
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtUnionV0(ScSpecUdtUnionV0 {
+                name: "Key".as_bytes().try_into().unwrap(),
+                cases: vec![
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Admin".as_bytes().try_into().unwrap(),
+                        type_: None,
+                    },
+                    ScSpecUdtUnionCaseV0 {
+                        name: "Balance".as_bytes().try_into().unwrap(),
+                        type_: Some(ScSpecTypeDef::U32),
+                    },
+                ]
+                .try_into()
+                .unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_struct() {
+        let spec = spec();
+        let expected = ScVal::Object(Some(ScObject::Map(
+            ScMap::sorted_from(vec![
+                ScMapEntry {
+                    key: symbol("y"),
+                    val: ScVal::U32(2),
+                },
+                ScMapEntry {
+                    key: symbol("x"),
+                    val: ScVal::U32(1),
+                },
+            ])
+            .unwrap(),
+        )));
+        assert_eq!(
+            from_json(&json!({"x": 1, "y": 2}), &udt("Point"), &spec).unwrap(),
+            expected
+        );
+        assert_eq!(
+            from_string(r#"{"x":1,"y":2}"#, &udt("Point"), &spec).unwrap(),
+            expected
+        );
+        for bad in [
+            json!({"x": 1}),
+            json!({"x": 1, "y": 2, "z": 3}),
+            json!([1, 2]),
+        ] {
+            assert!(from_json(&bad, &udt("Point"), &spec).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        let spec = spec();
+        assert_eq!(
+            from_json(&json!("Admin"), &udt("Key"), &spec).unwrap(),
+            vec(vec![symbol("Admin")])
+        );
+        assert_eq!(
+            from_string("Admin", &udt("Key"), &spec).unwrap(),
+            vec(vec![symbol("Admin")])
+        );
+        assert_eq!(
+            from_string(r#"{"Balance":7}"#, &udt("Key"), &spec).unwrap(),
+            vec(vec![symbol("Balance"), ScVal::U32(7)])
+        );
+        for bad in [
+            json!("Balance"),
+            json!({"Admin": 1}),
+            json!("Other"),
+            json!({"Admin": null, "Balance": 1}),
+        ] {
+            assert!(from_json(&bad, &udt("Key"), &spec).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_to_json_of_type_round_trips() {
+        let spec = spec();
+        let values = [
+            (json!({"x": 1, "y": 2}), udt("Point")),
+            (json!("Admin"), udt("Key")),
+            (json!({"Balance": 7}), udt("Key")),
+            (
+                json!([1, 2]),
+                ScSpecTypeDef::Set(Box::new(ScSpecTypeSet {
+                    element_type: ScSpecTypeDef::U32,
+                })),
+            ),
+            (json!(6), ScSpecTypeDef::Bitset),
+            (
+                json!(null),
+                ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                    value_type: udt("Key"),
+                })),
+            ),
+            (
+                json!({"1": "Admin"}),
+                ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+                    key_type: ScSpecTypeDef::U32,
+                    value_type: udt("Key"),
+                })),
+            ),
+        ];
+        for (json, t) in &values {
+            let v = from_json(json, t, &spec).unwrap();
+            assert_eq!(&to_json_of_type(&v, t, &spec).unwrap(), json);
+        }
+        assert!(to_json_of_type(&ScVal::U32(1), &udt("Key"), &spec).is_err());
+    }
+
+    #[test]
+    fn test_option() {
+        let t = ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+            value_type: ScSpecTypeDef::U32,
+        }));
+        assert_eq!(from_string("5", &t, &[]).unwrap(), ScVal::U32(5));
+        assert_eq!(from_string("null", &t, &[]).unwrap(), ScVal::Object(None));
+    }
+
+    #[test]
+    fn test_set_and_bitset() {
+        let t = ScSpecTypeDef::Set(Box::new(ScSpecTypeSet {
+            element_type: ScSpecTypeDef::U32,
+        }));
+        assert_eq!(
+            from_string("[1,2]", &t, &[]).unwrap(),
+            vec(vec![ScVal::U32(1), ScVal::U32(2)])
+        );
+        assert_eq!(
+            from_string("6", &ScSpecTypeDef::Bitset, &[]).unwrap(),
+            ScVal::Bitset(6)
+        );
+        assert!(from_string("-1", &ScSpecTypeDef::Bitset, &[]).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! A minimal contract, assembled section by section, for tests that need a WASM the host
+//! accepts.
+
+use soroban_env_host::{
+    meta,
+    xdr::{ScEnvMetaEntry, ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, WriteXdr},
+};
+
+use crate::wasm::{self, RawSection};
+
+const HEADER: &[u8] = b"\0asm\x01\0\0\0";
+
+const TYPE: u8 = 1;
+const FUNCTION: u8 = 3;
+const MEMORY: u8 = 5;
+const EXPORT: u8 = 7;
+const CODE: u8 = 10;
+
+/// The value `hello` returns, encoded as the host's raw u63.
+pub const HELLO_RESULT: i64 = 7;
+
+/// The spec of the contract, a single `fn hello() -> u64`.
+pub fn spec() -> Vec<ScSpecEntry> {
+    vec![ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+        name: "hello".as_bytes().try_into().unwrap(),
+        inputs: vec![].try_into().unwrap(),
+        outputs: vec![ScSpecTypeDef::U64].try_into().unwrap(),
+    })]
+}
+
+/// Builds a contract exporting `hello`, which is in the spec, `unused`, which isn't, and its
+/// memory. It has the interface version the host expects, the spec, and a custom section named
+/// `extra`.
+pub fn contract() -> Vec<u8> {
+    // One type, () -> i64
+    let types = [1, 0x60, 0, 1, 0x7e];
+    // Two functions of that type
+    let functions = [2, 0, 0];
+    // One page of memory, with no maximum
+    let memory = [1, 0, 1];
+    let exports = [
+        3, 5, b'h', b'e', b'l', b'l', b'o', 0, 0, 6, b'u', b'n', b'u', b's', b'e', b'd', 0, 1, 6,
+        b'm', b'e', b'm', b'o', b'r', b'y', 2, 0,
+    ];
+    // Each body has no locals and returns an i64.const, the u63 shifted left past the tag bit
+    let raw = u8::try_from(HELLO_RESULT << 1).unwrap();
+    let code = [2, 4, 0, 0x42, raw, 0x0b, 4, 0, 0x42, 0, 0x0b];
+
+    let meta = ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(meta::INTERFACE_VERSION)
+        .to_xdr()
+        .unwrap();
+    let mut spec = vec![];
+    for entry in spec() {
+        spec.extend(entry.to_xdr().unwrap());
+    }
+    let meta = RawSection::custom("contractenvmetav0", &meta);
+    let spec = RawSection::custom("contractspecv0", &spec);
+    let extra = RawSection::custom("extra", b"data");
+
+    wasm::encode_sections(
+        HEADER,
+        [
+            (TYPE, &types[..]),
+            (FUNCTION, &functions[..]),
+            (MEMORY, &memory[..]),
+            (EXPORT, &exports[..]),
+            (CODE, &code[..]),
+            (0, &meta[..]),
+            (0, &spec[..]),
+            (0, &extra[..]),
+        ],
+    )
+}
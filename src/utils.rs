@@ -1,24 +1,33 @@
-use hex::FromHexError;
+use std::{path::PathBuf, rc::Rc};
+
+use sha2::{Digest, Sha256};
 use soroban_env_host::{
     im_rc::OrdMap,
     storage::Storage,
     xdr::{
-        ContractDataEntry, Error as XdrError, LedgerEntry, LedgerEntryData, LedgerEntryExt,
-        LedgerKey, LedgerKeyContractData, ScContractCode, ScObject, ScStatic, ScStatus,
-        ScUnknownErrorCode, ScVal,
+        AccountId, ContractDataEntry, Error as XdrError, HashIdPreimage,
+        HashIdPreimageEd25519ContractId, HashIdPreimageSourceAccountContractId, LedgerEntry,
+        LedgerEntryData, LedgerEntryExt, LedgerKey, LedgerKeyContractData, PublicKey,
+        ScContractCode, ScObject, ScStatic, ScStatus, ScUnknownErrorCode, ScVal, Uint256, WriteXdr,
     },
     HostError,
 };
 
+use crate::snapshot;
+
+pub fn contract_code_key(contract_id: [u8; 32]) -> LedgerKey {
+    LedgerKey::ContractData(LedgerKeyContractData {
+        contract_id: contract_id.into(),
+        key: ScVal::Static(ScStatic::LedgerKeyContractCode),
+    })
+}
+
 pub fn add_contract_to_ledger_entries(
     entries: &mut OrdMap<LedgerKey, LedgerEntry>,
     contract_id: [u8; 32],
     contract: Vec<u8>,
 ) -> Result<(), XdrError> {
-    let key = LedgerKey::ContractData(LedgerKeyContractData {
-        contract_id: contract_id.into(),
-        key: ScVal::Static(ScStatic::LedgerKeyContractCode),
-    });
+    let key = contract_code_key(contract_id);
 
     let data = LedgerEntryData::ContractData(ContractDataEntry {
         contract_id: contract_id.into(),
@@ -38,21 +47,149 @@ pub fn add_contract_to_ledger_entries(
     Ok(())
 }
 
-pub fn contract_id_from_str(contract_id: &String) -> Result<[u8; 32], FromHexError> {
+#[derive(thiserror::Error, Debug)]
+pub enum ContractIdError {
+    #[error("contract ID {0} must be 64 hex characters or a C... strkey")]
+    InvalidLength(String),
+    #[error("contract ID {0} is not valid hex")]
+    InvalidHex(String),
+    #[error("contract ID {0} is not a valid contract strkey")]
+    InvalidStrkey(String),
+}
+
+/// Parses a contract ID given as exactly 64 hex characters or as a C... strkey. With
+/// `allow_short`, shorter hex strings are left-padded with zeros.
+pub fn contract_id_from_str(
+    contract_id: &str,
+    allow_short: bool,
+) -> Result<[u8; 32], ContractIdError> {
+    // 64 hex characters can start with C too, so strkeys are told apart by length. A short hex
+    // ID can be as long as a strkey, so it's parsed as hex if it isn't a valid strkey.
+    if contract_id.len() == STRKEY_CONTRACT_LEN {
+        if let Some(decoded) = contract_id_from_strkey(contract_id) {
+            return Ok(decoded);
+        }
+        if !allow_short || !contract_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ContractIdError::InvalidStrkey(contract_id.to_string()));
+        }
+    }
     let mut decoded = [0u8; 32];
-    let padded = format!("{:0>width$}", contract_id, width = decoded.len() * 2);
-    hex::decode_to_slice(padded, &mut decoded)?;
+    let width = decoded.len() * 2;
+    if contract_id.len() > width || (contract_id.len() < width && !allow_short) {
+        return Err(ContractIdError::InvalidLength(contract_id.to_string()));
+    }
+    let padded = format!("{:0>width$}", contract_id, width = width);
+    hex::decode_to_slice(padded, &mut decoded)
+        .map_err(|_| ContractIdError::InvalidHex(contract_id.to_string()))?;
     Ok(decoded)
 }
 
+const STRKEY_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const STRKEY_VERSION_CONTRACT: u8 = 2 << 3;
+/// Length of a contract strkey: a version byte, the 32 byte ID and a 2 byte checksum, which is
+/// exactly 56 base32 characters with no padding bits.
+const STRKEY_CONTRACT_LEN: usize = 56;
+
+/// Encodes a contract ID as a C... strkey.
+pub fn contract_id_to_strkey(contract_id: &[u8; 32]) -> String {
+    let mut payload = Vec::with_capacity(contract_id.len() + 3);
+    payload.push(STRKEY_VERSION_CONTRACT);
+    payload.extend_from_slice(contract_id);
+    let checksum = crc16_xmodem(&payload);
+    payload.extend_from_slice(&checksum.to_le_bytes());
+
+    let mut encoded = String::with_capacity((payload.len() * 8 + 4) / 5);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for b in payload {
+        buffer = (buffer << 8) | u32::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(STRKEY_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(STRKEY_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    encoded
+}
+
+fn contract_id_from_strkey(s: &str) -> Option<[u8; 32]> {
+    if s.len() != STRKEY_CONTRACT_LEN {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = STRKEY_ALPHABET.iter().position(|&a| a == c)?;
+        buffer = (buffer << 5) | u32::try_from(v).ok()?;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+        }
+    }
+    if bits != 0 {
+        return None;
+    }
+
+    let (payload, checksum) = decoded.split_at(decoded.len().checked_sub(2)?);
+    let (version, contract_id) = payload.split_first()?;
+    if *version != STRKEY_VERSION_CONTRACT || checksum != crc16_xmodem(payload).to_le_bytes() {
+        return None;
+    }
+    contract_id.try_into().ok()
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for b in data {
+        crc ^= u16::from(*b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x1021
+            };
+        }
+    }
+    crc
+}
+
+/// Derives a contract ID the way the network does for contracts created by an ed25519 key.
+pub fn contract_id_from_ed25519(key: [u8; 32], salt: [u8; 32]) -> Result<[u8; 32], XdrError> {
+    let preimage = HashIdPreimage::ContractIdFromEd25519(HashIdPreimageEd25519ContractId {
+        ed25519: Uint256(key),
+        salt: Uint256(salt),
+    });
+    Ok(Sha256::digest(preimage.to_xdr()?).into())
+}
+
+/// Derives a contract ID the way the network does for contracts created by a source account.
+pub fn contract_id_from_source_account(
+    account: [u8; 32],
+    salt: [u8; 32],
+) -> Result<[u8; 32], XdrError> {
+    let preimage =
+        HashIdPreimage::ContractIdFromSourceAccount(HashIdPreimageSourceAccountContractId {
+            source_account: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(account))),
+            salt: Uint256(salt),
+        });
+    Ok(Sha256::digest(preimage.to_xdr()?).into())
+}
+
+/// Derives a content-addressed contract ID from the contract's code.
+pub fn contract_id_from_wasm(contract: &[u8]) -> [u8; 32] {
+    Sha256::digest(contract).into()
+}
+
 pub fn get_contract_wasm_from_storage(
     storage: &mut Storage,
     contract_id: [u8; 32],
 ) -> Result<Vec<u8>, HostError> {
-    let key = LedgerKey::ContractData(LedgerKeyContractData {
-        contract_id: contract_id.into(),
-        key: ScVal::Static(ScStatic::LedgerKeyContractCode),
-    });
+    let key = contract_code_key(contract_id);
     if let LedgerEntryData::ContractData(entry) = storage.get(&key)?.data {
         if let ScVal::Object(Some(ScObject::ContractCode(ScContractCode::Wasm(data)))) = entry.val {
             return Ok(data.to_vec());
@@ -62,3 +199,157 @@ pub fn get_contract_wasm_from_storage(
         ScUnknownErrorCode::General,
     )))
 }
+
+pub fn get_contract_wasm_from_ledger_entries(
+    entries: &OrdMap<LedgerKey, LedgerEntry>,
+    contract_id: [u8; 32],
+) -> Option<Vec<u8>> {
+    if let LedgerEntryData::ContractData(entry) =
+        &entries.get(&contract_code_key(contract_id))?.data
+    {
+        if let ScVal::Object(Some(ScObject::ContractCode(ScContractCode::Wasm(data)))) = &entry.val
+        {
+            return Some(data.to_vec());
+        }
+    }
+    None
+}
+
+/// Finds the ID of a contract in the ledger that has the WASM as its code.
+pub fn find_contract_id_with_wasm(
+    entries: &OrdMap<LedgerKey, LedgerEntry>,
+    contract: &[u8],
+) -> Option<[u8; 32]> {
+    entries
+        .iter()
+        .find_map(|(key, entry)| match (key, &entry.data) {
+            (
+                LedgerKey::ContractData(LedgerKeyContractData {
+                    contract_id,
+                    key: ScVal::Static(ScStatic::LedgerKeyContractCode),
+                }),
+                LedgerEntryData::ContractData(data),
+            ) => match &data.val {
+                ScVal::Object(Some(ScObject::ContractCode(ScContractCode::Wasm(code))))
+                    if code.as_slice() == contract =>
+                {
+                    Some(contract_id.0)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+pub fn get_contract_wasm_from_ledger_file(
+    ledger_file: &PathBuf,
+    contract_id: [u8; 32],
+) -> Result<Vec<u8>, snapshot::Error> {
+    let ledger_entries = snapshot::read(ledger_file)?;
+    let snap = Rc::new(snapshot::Snap { ledger_entries });
+    let mut storage = Storage::with_recording_footprint(snap);
+    Ok(get_contract_wasm_from_storage(&mut storage, contract_id)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STRKEY: &str = "CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE";
+    const HEX: &str = "363eaa3867841fbad0f4ed88c779e4fe66e56a2470dc98c0ec9c073d05c7b103";
+
+    fn id(hex_id: &str) -> [u8; 32] {
+        let mut decoded = [0u8; 32];
+        hex::decode_to_slice(hex_id, &mut decoded).unwrap();
+        decoded
+    }
+
+    #[test]
+    fn test_find_contract_id_with_wasm() {
+        let mut entries = OrdMap::new();
+        add_contract_to_ledger_entries(&mut entries, [1; 32], vec![0, 1]).unwrap();
+        add_contract_to_ledger_entries(&mut entries, [2; 32], vec![0, 2]).unwrap();
+        assert_eq!(find_contract_id_with_wasm(&entries, &[0, 2]), Some([2; 32]));
+        assert_eq!(find_contract_id_with_wasm(&entries, &[0, 3]), None);
+    }
+
+    #[test]
+    fn test_crc16_xmodem() {
+        assert_eq!(crc16_xmodem(b"123456789"), 0x31c3);
+        assert_eq!(crc16_xmodem(b""), 0);
+    }
+
+    #[test]
+    fn test_strkey_known_vectors() {
+        assert_eq!(contract_id_to_strkey(&id(HEX)), STRKEY);
+        assert_eq!(contract_id_from_strkey(STRKEY), Some(id(HEX)));
+        assert_eq!(
+            contract_id_to_strkey(&[0; 32]),
+            "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4"
+        );
+    }
+
+    #[test]
+    fn test_strkey_round_trip() {
+        for contract_id in [[0u8; 32], [0xff; 32], id(HEX)] {
+            let strkey = contract_id_to_strkey(&contract_id);
+            assert_eq!(strkey.len(), STRKEY_CONTRACT_LEN);
+            assert_eq!(contract_id_from_strkey(&strkey), Some(contract_id));
+        }
+    }
+
+    #[test]
+    fn test_strkey_rejects_invalid() {
+        // Too short and too long
+        assert_eq!(contract_id_from_strkey(&STRKEY[..55]), None);
+        assert_eq!(contract_id_from_strkey(&format!("{}A", STRKEY)), None);
+        // Checksum mismatch
+        assert_eq!(
+            contract_id_from_strkey("CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXA"),
+            None
+        );
+        // Lowercase and characters outside the alphabet
+        assert_eq!(contract_id_from_strkey(&STRKEY.to_lowercase()), None);
+        assert_eq!(
+            contract_id_from_strkey("CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAX1"),
+            None
+        );
+        // Account strkeys have a different version byte
+        assert_eq!(
+            contract_id_from_strkey("GA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQHES5"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_contract_id_from_str() {
+        assert_eq!(contract_id_from_str(STRKEY, false).unwrap(), id(HEX));
+        assert_eq!(contract_id_from_str(HEX, false).unwrap(), id(HEX));
+        // Hex that starts with C is still hex
+        let cafe = format!("CAFE{}", "0".repeat(60));
+        assert_eq!(contract_id_from_str(&cafe, false).unwrap(), id(&cafe));
+        assert!(matches!(
+            contract_id_from_str("1", false),
+            Err(ContractIdError::InvalidLength(_))
+        ));
+        assert_eq!(contract_id_from_str("1", true).unwrap()[31], 1);
+        assert!(matches!(
+            contract_id_from_str(&"z".repeat(64), false),
+            Err(ContractIdError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            contract_id_from_str(&STRKEY.replace('X', "Y"), false),
+            Err(ContractIdError::InvalidStrkey(_))
+        ));
+        // A short hex ID as long as a strkey is hex when short IDs are allowed
+        let short = "c".repeat(56);
+        assert_eq!(
+            contract_id_from_str(&short, true).unwrap(),
+            id(&format!("00000000{}", short))
+        );
+        assert!(matches!(
+            contract_id_from_str(&short, false),
+            Err(ContractIdError::InvalidStrkey(_))
+        ));
+    }
+}
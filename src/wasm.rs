@@ -0,0 +1,437 @@
+use std::{fmt::Debug, fs, io, io::Cursor, path::PathBuf};
+
+use clap::Parser;
+use soroban_env_host::xdr::{self, ReadXdr, ScEnvMetaEntry, ScSpecEntry};
+
+use crate::snapshot;
+use crate::utils;
+
+/// Arguments for commands that operate on a contract's WASM, which can either be read from a
+/// file or from the code of a contract deployed in the ledger.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// WASM file
+    #[clap(
+        long,
+        parse(from_os_str),
+        conflicts_with = "contract-id",
+        required_unless_present = "contract-id"
+    )]
+    pub wasm: Option<PathBuf>,
+    /// Contract ID to read the WASM from, instead of a file
+    #[clap(long = "id")]
+    pub contract_id: Option<String>,
+    /// File to read ledger state from, when using --id
+    #[clap(long, parse(from_os_str), default_value(".soroban/ledger.json"))]
+    pub ledger_file: PathBuf,
+    /// Allow contract IDs shorter than 64 hex characters, left-padding them with zeros
+    #[clap(long)]
+    pub allow_short_id: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("reading file {filepath}: {error}")]
+    CannotReadContractFile { filepath: PathBuf, error: io::Error },
+    #[error(transparent)]
+    CannotParseContractId(#[from] utils::ContractIdError),
+    #[error("loading contract {contract_id} from {filepath}: {error}")]
+    CannotLoadContract {
+        contract_id: String,
+        filepath: PathBuf,
+        error: snapshot::Error,
+    },
+}
+
+impl Args {
+    /// Describes where the WASM is read from, for display.
+    pub fn source(&self) -> String {
+        match (&self.wasm, &self.contract_id) {
+            (Some(wasm), _) => wasm.to_string_lossy().to_string(),
+            (None, Some(contract_id)) => format!(
+                "contract {} in {}",
+                contract_id,
+                self.ledger_file.to_string_lossy()
+            ),
+            (None, None) => String::new(),
+        }
+    }
+
+    pub fn read(&self) -> Result<Vec<u8>, Error> {
+        if let Some(wasm) = &self.wasm {
+            return fs::read(wasm).map_err(|e| Error::CannotReadContractFile {
+                filepath: wasm.clone(),
+                error: e,
+            });
+        }
+        let contract_id = self.contract_id.clone().unwrap_or_default();
+        from_ledger(&self.ledger_file, &contract_id, self.allow_short_id)
+    }
+}
+
+/// Reads the WASM of the contract deployed at the contract ID in the ledger file.
+pub fn from_ledger(
+    ledger_file: &PathBuf,
+    contract_id: &str,
+    allow_short_id: bool,
+) -> Result<Vec<u8>, Error> {
+    let id = utils::contract_id_from_str(contract_id, allow_short_id)?;
+    utils::get_contract_wasm_from_ledger_file(ledger_file, id).map_err(|e| {
+        Error::CannotLoadContract {
+            contract_id: contract_id.to_string(),
+            filepath: ledger_file.clone(),
+            error: e,
+        }
+    })
+}
+
+/// The parts of a contract's WASM module that the CLI looks at.
+#[derive(Default)]
+pub struct Module<'a> {
+    /// Contents of the `contractenvmetav0` custom section
+    pub env_meta: Option<&'a [u8]>,
+    /// Contents of the `contractspecv0` custom section
+    pub spec: Option<&'a [u8]>,
+    /// Contents of the `contractmetav0` custom section
+    pub meta: Option<&'a [u8]>,
+    pub exports: Vec<wasmparser::Export<'a>>,
+    pub imports: Vec<wasmparser::Import<'a>>,
+    pub memories: Vec<wasmparser::MemoryType>,
+    pub tables: Vec<wasmparser::TableType>,
+    /// Names and sizes in bytes of all custom sections, in module order
+    pub custom_sections: Vec<(&'a str, usize)>,
+    /// Sizes in bytes of the bodies of the functions defined in the module, which are indexed
+    /// after the imported functions
+    pub function_body_sizes: Vec<usize>,
+    /// Sizes in bytes of the data segments
+    pub data_segment_sizes: Vec<usize>,
+    /// Index of the start function
+    pub start_function: Option<u32>,
+}
+
+impl<'a> Module<'a> {
+    /// Names of the exported functions
+    pub fn function_exports(&self) -> Vec<&'a str> {
+        self.exports
+            .iter()
+            .filter(|e| e.kind == wasmparser::ExternalKind::Func)
+            .map(|e| e.name)
+            .collect()
+    }
+
+    /// Number of imported functions, which is the index of the first function defined in the
+    /// module
+    pub fn function_imports_count(&self) -> usize {
+        self.imports
+            .iter()
+            .filter(|i| matches!(i.ty, wasmparser::TypeRef::Func(_)))
+            .count()
+    }
+
+    pub fn env_meta_entries(&self) -> Result<Vec<ScEnvMetaEntry>, xdr::Error> {
+        let mut cursor = Cursor::new(self.env_meta.unwrap_or_default());
+        ScEnvMetaEntry::read_xdr_iter(&mut cursor).collect()
+    }
+
+    pub fn interface_version(&self) -> Result<Option<u64>, xdr::Error> {
+        Ok(self
+            .env_meta_entries()?
+            .into_iter()
+            .next()
+            .map(|e| match e {
+                ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(v) => v,
+            }))
+    }
+
+    pub fn spec_entries(&self) -> Result<Vec<ScSpecEntry>, xdr::Error> {
+        let mut cursor = Cursor::new(self.spec.unwrap_or_default());
+        ScSpecEntry::read_xdr_iter(&mut cursor).collect()
+    }
+
+    /// The key and value of each entry of the contract meta, which the SDK writes as a stream of
+    /// xdr `SCMetaEntry`. The xdr this CLI is built with doesn't define it, so it's decoded here,
+    /// and `None` is returned if it's malformed.
+    pub fn meta_entries(&self) -> Option<Vec<(String, String)>> {
+        let meta = self.meta.unwrap_or_default();
+        let mut entries = vec![];
+        let mut pos = 0;
+        while pos < meta.len() {
+            // Only SC_META_V0 entries, a key and a value string, are defined
+            if read_xdr_u32(meta, &mut pos)? != 0 {
+                return None;
+            }
+            let key = read_xdr_string(meta, &mut pos)?;
+            let val = read_xdr_string(meta, &mut pos)?;
+            entries.push((key, val));
+        }
+        Some(entries)
+    }
+}
+
+fn read_xdr_u32(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let b = bytes.get(*pos..*pos + 4)?;
+    *pos += 4;
+    Some(u32::from_be_bytes(b.try_into().ok()?))
+}
+
+/// Reads an xdr string, which is its length followed by its bytes, padded to a multiple of 4.
+fn read_xdr_string(bytes: &[u8], pos: &mut usize) -> Option<String> {
+    let len = usize::try_from(read_xdr_u32(bytes, pos)?).ok()?;
+    let s = std::str::from_utf8(bytes.get(*pos..*pos + len)?).ok()?;
+    *pos += (len + 3) / 4 * 4;
+    if *pos > bytes.len() {
+        return None;
+    }
+    Some(s.to_string())
+}
+
+pub fn parse(contents: &[u8]) -> Result<Module, wasmparser::BinaryReaderError> {
+    let mut module = Module::default();
+    for payload in wasmparser::Parser::new(0).parse_all(contents) {
+        match payload? {
+            wasmparser::Payload::CustomSection(section) => {
+                module
+                    .custom_sections
+                    .push((section.name(), section.data().len()));
+                let out = match section.name() {
+                    "contractenvmetav0" => &mut module.env_meta,
+                    "contractspecv0" => &mut module.spec,
+                    "contractmetav0" => &mut module.meta,
+                    _ => continue,
+                };
+                *out = Some(section.data());
+            }
+            wasmparser::Payload::ExportSection(exports) => {
+                for export in exports {
+                    module.exports.push(export?);
+                }
+            }
+            wasmparser::Payload::ImportSection(imports) => {
+                for import in imports {
+                    module.imports.push(import?);
+                }
+            }
+            wasmparser::Payload::MemorySection(memories) => {
+                for memory in memories {
+                    module.memories.push(memory?);
+                }
+            }
+            wasmparser::Payload::TableSection(tables) => {
+                for table in tables {
+                    module.tables.push(table?);
+                }
+            }
+            wasmparser::Payload::CodeSectionEntry(body) => {
+                module.function_body_sizes.push(body.range().len());
+            }
+            wasmparser::Payload::DataSection(data) => {
+                for segment in data {
+                    module.data_segment_sizes.push(segment?.data.len());
+                }
+            }
+            wasmparser::Payload::StartSection { func, .. } => {
+                module.start_function = Some(func);
+            }
+            _ => {}
+        }
+    }
+    Ok(module)
+}
+
+/// A section of a WASM module as it is encoded, so that modules can be rewritten without
+/// touching the sections that aren't changed.
+pub struct RawSection<'a> {
+    pub id: u8,
+    /// Contents of the section, excluding the id and size
+    pub contents: &'a [u8],
+}
+
+impl<'a> RawSection<'a> {
+    const CUSTOM: u8 = 0;
+    const EXPORT: u8 = 7;
+
+    /// Name of the section if it is a custom section.
+    pub fn custom_name(&self) -> Option<&'a str> {
+        if self.id != Self::CUSTOM {
+            return None;
+        }
+        let mut pos = 0;
+        let len = usize::try_from(read_leb128_u32(self.contents, &mut pos)?).ok()?;
+        std::str::from_utf8(self.contents.get(pos..pos + len)?).ok()
+    }
+
+    /// Builds a custom section with the name and data.
+    pub fn custom(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut contents = vec![];
+        write_leb128_u32(&mut contents, name.len());
+        contents.extend_from_slice(name.as_bytes());
+        contents.extend_from_slice(data);
+        contents
+    }
+
+    /// Builds an export section with the exports.
+    pub fn exports(exports: &[wasmparser::Export]) -> Vec<u8> {
+        let mut contents = vec![];
+        write_leb128_u32(&mut contents, exports.len());
+        for e in exports {
+            write_leb128_u32(&mut contents, e.name.len());
+            contents.extend_from_slice(e.name.as_bytes());
+            contents.push(match e.kind {
+                wasmparser::ExternalKind::Func => 0,
+                wasmparser::ExternalKind::Table => 1,
+                wasmparser::ExternalKind::Memory => 2,
+                wasmparser::ExternalKind::Global => 3,
+                wasmparser::ExternalKind::Tag => 4,
+            });
+            write_leb128_u32(&mut contents, e.index as usize);
+        }
+        contents
+    }
+
+    pub fn is_export(&self) -> bool {
+        self.id == Self::EXPORT
+    }
+}
+
+const HEADER_LEN: usize = 8;
+
+/// Splits a module into its sections, returning `None` if the module is malformed.
+pub fn raw_sections(contents: &[u8]) -> Option<Vec<RawSection>> {
+    let mut sections = vec![];
+    let mut pos = HEADER_LEN;
+    contents.get(..HEADER_LEN)?;
+    while pos < contents.len() {
+        let id = contents[pos];
+        pos += 1;
+        let len = usize::try_from(read_leb128_u32(contents, &mut pos)?).ok()?;
+        sections.push(RawSection {
+            id,
+            contents: contents.get(pos..pos + len)?,
+        });
+        pos += len;
+    }
+    Some(sections)
+}
+
+/// Reassembles a module from the header of the original module and the sections, given as
+/// pairs of section id and contents.
+pub fn encode_sections<'a, I>(original: &[u8], sections: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = (u8, &'a [u8])>,
+{
+    let mut out = original[..HEADER_LEN].to_vec();
+    for (id, contents) in sections {
+        out.push(id);
+        write_leb128_u32(&mut out, contents.len());
+        out.extend_from_slice(contents);
+    }
+    out
+}
+
+fn read_leb128_u32(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut result: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let b = *bytes.get(*pos)?;
+        *pos += 1;
+        result |= u32::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_leb128_u32(out: &mut Vec<u8>, value: usize) {
+    let mut value = value;
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(b);
+            return;
+        }
+        out.push(b | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEADER: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn test_leb128() {
+        let mut out = vec![];
+        write_leb128_u32(&mut out, 624_485);
+        assert_eq!(out, [0xe5, 0x8e, 0x26]);
+
+        for value in [0, 1, 127, 128, 300, 16_384, u32::MAX] {
+            let mut out = vec![];
+            write_leb128_u32(&mut out, value as usize);
+            let mut pos = 0;
+            assert_eq!(read_leb128_u32(&out, &mut pos), Some(value));
+            assert_eq!(pos, out.len());
+        }
+
+        // Truncated, and longer than a u32 can be
+        assert_eq!(read_leb128_u32(&[0x80], &mut 0), None);
+        assert_eq!(read_leb128_u32(&[0x80; 6], &mut 0), None);
+    }
+
+    #[test]
+    fn test_sections_round_trip() {
+        let custom = RawSection::custom("contractspecv0", &[1, 2, 3]);
+        let export = [1, 1, b'f', 0, 0];
+        let module = encode_sections(HEADER, [(0, custom.as_slice()), (7, export.as_slice())]);
+
+        let sections = raw_sections(&module).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].custom_name(), Some("contractspecv0"));
+        assert_eq!(sections[0].contents, custom.as_slice());
+        assert!(!sections[0].is_export());
+        assert_eq!(sections[1].custom_name(), None);
+        assert!(sections[1].is_export());
+
+        let reencoded = encode_sections(&module, sections.iter().map(|s| (s.id, s.contents)));
+        assert_eq!(reencoded, module);
+    }
+
+    #[test]
+    fn test_exports_section() {
+        let module = encode_sections(HEADER, [(7, [1, 1, b'f', 0, 0].as_slice())]);
+        let exports = parse(&module).unwrap().exports;
+        assert_eq!(RawSection::exports(&exports), [1, 1, b'f', 0, 0]);
+    }
+
+    #[test]
+    fn test_raw_sections_malformed() {
+        assert!(raw_sections(&HEADER[..4]).is_none());
+        // Section claims more contents than there are
+        let mut module = HEADER.to_vec();
+        module.extend_from_slice(&[0, 5, 1]);
+        assert!(raw_sections(&module).is_none());
+    }
+
+    #[test]
+    fn test_meta_entries() {
+        let mut meta = vec![0, 0, 0, 0, 0, 0, 0, 7];
+        meta.extend_from_slice(b"version\0");
+        meta.extend_from_slice(&[0, 0, 0, 5]);
+        meta.extend_from_slice(b"1.2.0\0\0\0");
+        let custom = RawSection::custom("contractmetav0", &meta);
+        let module = encode_sections(HEADER, [(0, custom.as_slice())]);
+        assert_eq!(
+            parse(&module).unwrap().meta_entries(),
+            Some(vec![("version".to_string(), "1.2.0".to_string())])
+        );
+
+        // Truncated padding
+        let custom = RawSection::custom("contractmetav0", &meta[..meta.len() - 1]);
+        let module = encode_sections(HEADER, [(0, custom.as_slice())]);
+        assert_eq!(parse(&module).unwrap().meta_entries(), None);
+        // No meta at all
+        assert_eq!(parse(HEADER).unwrap().meta_entries(), Some(vec![]));
+    }
+}